@@ -0,0 +1,85 @@
+//! A minimal external plugin speaking the line delimited JSON protocol of
+//! centerpiece on stdin and stdout. Use it as a starting point for your own
+//! plugins or to try the protocol:
+//!
+//! ```yml
+//! plugin:
+//!   external:
+//!     - id: example
+//!       title: Example
+//!       command: ["cargo", "run", "--quiet", "--example", "external_plugin"]
+//!       update_interval: 5
+//! ```
+
+fn entry(id: &str, title: String, action: &str) -> serde_json::Value {
+    serde_json::json!({ "id": id, "title": title, "action": action, "meta": "Example" })
+}
+
+fn entries(title: &str, query: &str, generation: u64, updates: u32) -> serde_json::Value {
+    let mut hello = entry("hello", format!("Hello from {title}"), "greet");
+    hello["secondary_actions"] = serde_json::json!(["wave"]);
+    let mut entries = vec![
+        hello,
+        entry("exit", String::from("Exit centerpiece"), "exit"),
+    ];
+    if !query.is_empty() {
+        entries.push(entry(
+            "search",
+            format!("Search the web for '{query}'"),
+            "search",
+        ));
+    }
+    if updates > 0 {
+        entries.push(entry(
+            "updates",
+            format!("Updated {updates} time(s)"),
+            "none",
+        ));
+    }
+    serde_json::json!({ "type": "entries", "entries": entries, "generation": generation })
+}
+
+fn main() {
+    let mut title = String::new();
+    let mut query = String::new();
+    let mut generation = 0;
+    let mut updates = 0;
+
+    for line in std::io::stdin().lines().map_while(Result::ok) {
+        let request: serde_json::Value = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(error) => {
+                eprintln!("Ignoring invalid request '{line}': {error}");
+                continue;
+            }
+        };
+
+        let response = match request["type"].as_str() {
+            Some("register") => {
+                title = String::from(request["title"].as_str().unwrap_or_default());
+                entries(&title, &query, generation, updates)
+            }
+            Some("search") => {
+                query = String::from(request["query"].as_str().unwrap_or_default());
+                generation = request["generation"].as_u64().unwrap_or_default();
+                entries(&title, &query, generation, updates)
+            }
+            Some("timeout") => {
+                updates += 1;
+                entries(&title, &query, generation, updates)
+            }
+            Some("activate") if request["entry"]["id"] == "exit" => {
+                serde_json::json!({ "type": "exit" })
+            }
+            Some("activate") => {
+                eprintln!("Ran {} on {}", request["action"], request["entry"]);
+                continue;
+            }
+            _ => {
+                eprintln!("Ignoring unknown request '{line}'");
+                continue;
+            }
+        };
+        println!("{response}");
+    }
+}
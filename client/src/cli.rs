@@ -11,6 +11,11 @@ pub(crate) struct CliArgs {
         env = "CENTERPIECE_CONFIGURATION_FILE"
     )]
     pub(crate) config: Option<String>,
+    #[clap(
+        long,
+        help = "List the built-in plugins with their settings key and exit"
+    )]
+    pub(crate) list_plugins: bool,
 }
 
 impl CliArgs {
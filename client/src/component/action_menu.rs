@@ -0,0 +1,35 @@
+/// The actions of an entry, listed under its title.
+pub fn view(
+    entry: &crate::model::Entry,
+    actions: &[crate::model::Action],
+    selected_action_index: usize,
+) -> iced::Element<'static, crate::Message> {
+    iced::widget::column![
+        iced::widget::container(
+            iced::widget::text(&entry.title)
+                .font(crate::component::plugin::font())
+                .size(0.75 * crate::REM)
+        )
+        .padding(0.5 * crate::REM),
+        iced::widget::column(
+            actions
+                .iter()
+                .enumerate()
+                .map(|(index, action)| {
+                    let active = index == selected_action_index;
+                    iced::widget::container(
+                        iced::widget::text(&action.title)
+                            .font(crate::component::entry::font(false))
+                            .size(1. * crate::REM),
+                    )
+                    .width(iced::Length::Fill)
+                    .padding(0.5 * crate::REM)
+                    .style(crate::component::entry::style(active))
+                    .into()
+                })
+                .collect()
+        )
+    ]
+    .padding(0.75 * crate::REM)
+    .into()
+}
@@ -1,32 +1,112 @@
-pub fn view(entry: &crate::model::Entry, active: bool) -> iced::Element<'static, crate::Message> {
-    return iced::widget::container(
-        iced::widget::row![
-            iced::widget::text(clipped_title(entry.title.clone()))
-                .size(1. * crate::REM)
-                .width(iced::Length::Fill),
-            iced::widget::text(if active { &entry.action } else { "" }).size(1. * crate::REM)
-        ]
-        .padding(0.5 * crate::REM),
-    )
-    .style(style(active))
-    .into();
+/// Renders an entry. With `icon_column` the space of the icon is kept even if
+/// the entry has none, so titles line up with those of entries that have one.
+pub fn view(
+    entry: &crate::model::Entry,
+    active: bool,
+    badge: Option<&str>,
+    icon_column: bool,
+) -> iced::Element<'static, crate::Message> {
+    let mut view = iced::widget::row![].align_items(iced::Alignment::Center);
+
+    if let Some(icon) = &entry.icon {
+        let icon: iced::Element<'static, crate::Message> =
+            if icon.extension().is_some_and(|extension| extension == "png") {
+                iced::widget::image(iced::widget::image::Handle::from_path(icon))
+                    .width(ICON_SIZE * crate::REM)
+                    .height(ICON_SIZE * crate::REM)
+                    .into()
+            } else {
+                iced::widget::svg(iced::widget::svg::Handle::from_path(icon))
+                    .width(ICON_SIZE * crate::REM)
+                    .height(ICON_SIZE * crate::REM)
+                    .into()
+            };
+        view = view.push(icon);
+    } else if icon_column {
+        view = view.push(iced::widget::Space::with_width(ICON_SIZE * crate::REM));
+    }
+    if entry.icon.is_some() || icon_column {
+        view = view.push(iced::widget::Space::with_width(0.5 * crate::REM));
+    }
+
+    view = view.push(
+        iced::widget::Row::with_children(
+            title_segments(entry)
+                .into_iter()
+                .map(|(segment, highlighted)| {
+                    iced::widget::text(segment)
+                        .font(font(highlighted))
+                        .size(1. * crate::REM)
+                        .into()
+                })
+                .collect(),
+        )
+        .width(iced::Length::Fill),
+    );
+
+    if let Some(badge) = badge {
+        view = view.push(
+            iced::widget::container(
+                iced::widget::text(badge)
+                    .font(crate::component::plugin::font())
+                    .size(0.75 * crate::REM),
+            )
+            .padding(iced::Padding::from([0., 0.5 * crate::REM])),
+        );
+    }
+
+    view = view.push(
+        iced::widget::text(if active { entry.primary_action() } else { "" }).size(1. * crate::REM),
+    );
+
+    iced::widget::container(view.padding(0.5 * crate::REM))
+        .style(style(active))
+        .into()
 }
 
-fn clipped_title(title: String) -> String {
-    if title.char_indices().count() <= 57 {
-        return title;
+const ICON_SIZE: f32 = 1.25;
+
+const MAX_TITLE_LENGTH: usize = 57;
+
+/// Splits the clipped title into runs of highlighted and plain characters.
+fn title_segments(entry: &crate::model::Entry) -> Vec<(String, bool)> {
+    let mut segments: Vec<(String, bool)> = vec![];
+
+    for (index, character) in entry.title.chars().take(MAX_TITLE_LENGTH).enumerate() {
+        let highlighted = entry
+            .highlights
+            .iter()
+            .any(|highlight| highlight.contains(&index));
+
+        match segments.last_mut() {
+            Some((segment, segment_highlighted)) if *segment_highlighted == highlighted => {
+                segment.push(character)
+            }
+            _ => segments.push((String::from(character), highlighted)),
+        }
+    }
+
+    if entry.title.chars().count() > MAX_TITLE_LENGTH {
+        segments.push((String::from("..."), false));
     }
 
-    let mut clipped_title: String = title
-        .char_indices()
-        .map(|(_, character)| character)
-        .take(57)
-        .collect();
-    clipped_title.push_str("...");
-    clipped_title
+    segments
 }
 
-fn style(active: bool) -> iced::theme::Container {
+pub fn font(highlighted: bool) -> iced::Font {
+    iced::Font {
+        family: iced::font::Family::Name("FiraCode Nerd Font"),
+        weight: if highlighted {
+            iced::font::Weight::Bold
+        } else {
+            iced::font::Weight::Normal
+        },
+        stretch: iced::font::Stretch::Normal,
+        monospaced: true,
+    }
+}
+
+pub fn style(active: bool) -> iced::theme::Container {
     if active {
         iced::theme::Container::Custom(Box::new(Style {}))
     } else {
@@ -49,3 +129,70 @@ impl iced::widget::container::StyleSheet for Style {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    fn entry(title: &str, highlights: Vec<std::ops::Range<usize>>) -> crate::model::Entry {
+        crate::model::Entry {
+            id: String::from(title),
+            title: String::from(title),
+            actions: vec![],
+            meta: String::new(),
+            command: None,
+            kind: None,
+            icon: None,
+            highlights,
+            score: 0,
+            matched_with_typos: false,
+        }
+    }
+
+    fn segment(text: &str, highlighted: bool) -> (String, bool) {
+        (String::from(text), highlighted)
+    }
+
+    #[test]
+    fn highlights_survive_clipping_long_titles() {
+        let title = format!("{}Firefox{}", "a".repeat(50), "b".repeat(20));
+        // The second match lies in the clipped part of the title.
+        let segments = super::title_segments(&entry(&title, vec![50..57, 60..62]));
+
+        assert_eq!(
+            segments,
+            vec![
+                segment(&"a".repeat(50), false),
+                segment("Firefox", true),
+                segment("...", false),
+            ]
+        );
+        let visible_length: usize = segments
+            .iter()
+            .map(|(segment, _)| segment.chars().count())
+            .sum();
+        assert_eq!(visible_length, super::MAX_TITLE_LENGTH + 3);
+    }
+
+    #[test]
+    fn highlights_multi_byte_characters() {
+        let title = "Die Größe ändern";
+        let title_match = crate::search::fuzzy::fuzzy_match(
+            title,
+            "grosse an",
+            crate::search::fuzzy::Folding::Unicode,
+        )
+        .unwrap();
+        let highlights = crate::search::fuzzy::to_ranges(&title_match.positions);
+        assert_eq!(highlights, vec![4..9, 10..12]);
+
+        assert_eq!(
+            super::title_segments(&entry(title, highlights)),
+            vec![
+                segment("Die ", false),
+                segment("Größe", true),
+                segment(" ", false),
+                segment("än", true),
+                segment("dern", false),
+            ]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod action_menu;
 pub mod entry;
 pub mod plugin;
+pub mod preview;
 pub mod query_input;
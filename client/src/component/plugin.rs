@@ -1,9 +1,12 @@
+/// The plugin header followed by the entries of the plugin in display order.
 pub fn view(
     plugin: &crate::model::Plugin,
+    entries: &[&crate::model::Entry],
     add_horizontal_rule: bool,
     active_entry_id: Option<&String>,
 ) -> iced::Element<'static, crate::Message> {
     let mut view = iced::widget::column![];
+    let icon_column = entries.iter().any(|entry| entry.icon.is_some());
 
     if add_horizontal_rule {
         view = view.push(iced::widget::horizontal_rule(1));
@@ -11,23 +14,14 @@ pub fn view(
 
     view = view.push(
         iced::widget::column![
-            iced::widget::row![iced::widget::text(&plugin.title)
-                .font(iced::Font {
-                    family: iced::font::Family::Name("FiraCode Nerd Font"),
-                    weight: iced::font::Weight::Light,
-                    stretch: iced::font::Stretch::Normal,
-                    monospaced: true,
-                })
-                .size(0.75 * crate::REM)]
-            .padding(0.5 * crate::REM),
+            header(plugin),
             iced::widget::column(
-                plugin
-                    .entries
+                entries
                     .iter()
                     .map(|entry| {
                         let is_active =
                             active_entry_id.is_some() && active_entry_id.unwrap() == &entry.id;
-                        crate::component::entry::view(entry, is_active)
+                        crate::component::entry::view(entry, is_active, None, icon_column)
                     })
                     .collect()
             )
@@ -37,3 +31,40 @@ pub fn view(
 
     view.into()
 }
+
+const DIMMED_ALPHA: f32 = 0.5;
+
+/// The plugin title, followed by its status unless it is ready.
+pub fn header(plugin: &crate::model::Plugin) -> iced::Element<'static, crate::Message> {
+    let mut header = iced::widget::row![iced::widget::text(&plugin.title)
+        .font(font())
+        .size(0.75 * crate::REM)];
+
+    let status = match &plugin.status {
+        crate::model::PluginStatus::Loading => Some("loading…"),
+        crate::model::PluginStatus::Ready => None,
+        crate::model::PluginStatus::Error(message) => Some(message.as_str()),
+    };
+    if let Some(status) = status {
+        header = header.push(
+            iced::widget::text(format!(" — {status}"))
+                .font(font())
+                .size(0.75 * crate::REM)
+                .style(iced::Color {
+                    a: DIMMED_ALPHA,
+                    ..crate::TEXT_COLOR
+                }),
+        );
+    }
+
+    header.padding(0.5 * crate::REM).into()
+}
+
+pub fn font() -> iced::Font {
+    iced::Font {
+        family: iced::font::Family::Name("FiraCode Nerd Font"),
+        weight: iced::font::Weight::Light,
+        stretch: iced::font::Stretch::Normal,
+        monospaced: true,
+    }
+}
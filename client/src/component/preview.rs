@@ -0,0 +1,25 @@
+/// The details of the selected entry: labeled values followed by a text.
+pub fn view(preview: &crate::model::Preview) -> iced::Element<'static, crate::Message> {
+    let mut view = iced::widget::column![].spacing(0.5 * crate::REM);
+
+    for (label, value) in preview.fields.iter() {
+        view = view.push(iced::widget::column![
+            iced::widget::text(label)
+                .font(crate::component::plugin::font())
+                .size(0.75 * crate::REM),
+            iced::widget::text(value)
+                .font(crate::component::entry::font(false))
+                .size(1. * crate::REM),
+        ]);
+    }
+
+    if !preview.text.is_empty() {
+        view = view.push(
+            iced::widget::text(&preview.text)
+                .font(crate::component::entry::font(false))
+                .size(0.75 * crate::REM),
+        );
+    }
+
+    view.padding(0.75 * crate::REM).into()
+}
@@ -1,17 +1,51 @@
 pub const SEARCH_INPUT_ID: &str = "search_input";
 
-pub fn view(query: &str, add_horizontal_rule: bool) -> iced::Element<'static, crate::Message> {
-    let mut view = iced::widget::column![iced::widget::row![
-        iced::widget::container(iced::widget::text("󰍉 ").size(1.3 * crate::REM)).padding(
-            iced::Padding::from([0.2 * crate::REM, -0.3 * crate::REM, 0., 0.])
-        ),
+pub fn view(
+    query: &str,
+    add_horizontal_rule: bool,
+    scope: Option<&str>,
+) -> iced::Element<'static, crate::Message> {
+    let mut input_row = iced::widget::row![iced::widget::container(
+        iced::widget::text("󰍉 ").size(1.3 * crate::REM)
+    )
+    .padding(iced::Padding::from([
+        0.2 * crate::REM,
+        -0.3 * crate::REM,
+        0.,
+        0.
+    ]))]
+    .align_items(iced::Alignment::Center);
+
+    if let Some(scope) = scope {
+        input_row = input_row.push(
+            iced::widget::container(
+                iced::widget::text(scope)
+                    .font(iced::Font {
+                        family: iced::font::Family::Name("FiraCode Nerd Font"),
+                        weight: iced::font::Weight::Light,
+                        stretch: iced::font::Stretch::Normal,
+                        monospaced: true,
+                    })
+                    .size(0.75 * crate::REM),
+            )
+            .padding(iced::Padding::from([0.1 * crate::REM, 0.4 * crate::REM]))
+            .style(iced::theme::Container::Custom(Box::new(
+                crate::component::entry::Style {},
+            ))),
+        );
+    }
+
+    input_row = input_row.push(
         iced::widget::text_input("Search", query)
             .id(iced::widget::text_input::Id::new(SEARCH_INPUT_ID))
             .on_input(crate::Message::Search)
             .size(1. * crate::REM)
-            .style(style())
+            .style(style()),
+    );
+
+    let mut view = iced::widget::column![
+        input_row.padding(iced::Padding::from([0.8 * crate::REM, 1.2 * crate::REM]))
     ]
-    .padding(iced::Padding::from([0.8 * crate::REM, 1.2 * crate::REM])),]
     .padding(iced::Padding::from([0., 0., 1., 0.]));
 
     if add_horizontal_rule {
@@ -0,0 +1,158 @@
+use anyhow::Context;
+
+// The ids are prefixed to keep them apart from the action ids of plugins.
+pub const OPEN: &str = "generic:open";
+pub const REVEAL: &str = "generic:reveal";
+pub const TERMINAL: &str = "generic:terminal";
+pub const COPY: &str = "generic:copy";
+
+fn title(action: &str) -> &'static str {
+    match action {
+        OPEN => "open with xdg-open",
+        REVEAL => "reveal in file manager",
+        TERMINAL => "open terminal here",
+        COPY => "copy to clipboard",
+        _ => "",
+    }
+}
+
+/// The actions the app offers for entries of the kind, no matter which plugin
+/// they come from.
+pub fn for_kind(kind: &crate::model::EntryKind) -> Vec<crate::model::Action> {
+    let actions = match kind {
+        crate::model::EntryKind::Url(_) => vec![OPEN, COPY],
+        crate::model::EntryKind::Path(_) => vec![OPEN, REVEAL, TERMINAL, COPY],
+        crate::model::EntryKind::Command(_) | crate::model::EntryKind::Text(_) => vec![COPY],
+    };
+    actions
+        .into_iter()
+        .map(|action| crate::model::Action::new(action, title(action)))
+        .collect()
+}
+
+/// Runs a generic action on the payload of an entry.
+pub fn run(kind: &crate::model::EntryKind, action: &str) -> anyhow::Result<()> {
+    match (action, kind) {
+        (COPY, kind) => crate::plugin::utils::copy_to_clipboard(&kind.text()),
+        (OPEN, crate::model::EntryKind::Url(url)) => xdg_open(url),
+        (OPEN, crate::model::EntryKind::Path(path)) => xdg_open(path),
+        (REVEAL, crate::model::EntryKind::Path(path)) => reveal(path),
+        (TERMINAL, crate::model::EntryKind::Path(path)) => {
+            let directory = match path.is_dir() {
+                true => path.as_path(),
+                false => path.parent().unwrap_or(path),
+            };
+            std::process::Command::new(crate::plugin::utils::terminal())
+                .current_dir(directory)
+                .spawn()
+                .context("Failed to open a terminal.")?;
+            Ok(())
+        }
+        (action, kind) => Err(anyhow::anyhow!(
+            "The action '{}' is not available for {} entries.",
+            action,
+            kind.name()
+        )),
+    }
+}
+
+/// Shows the directory of the path in the file manager with the path selected.
+/// Opens just the directory if no file manager implements the D-Bus interface.
+fn reveal(path: &std::path::Path) -> anyhow::Result<()> {
+    if let Err(error) = show_items(path) {
+        log::warn!(
+            "Failed to reveal '{}' in the file manager, opening its directory instead: {:?}",
+            path.display(),
+            error
+        );
+        return xdg_open(path.parent().unwrap_or(path));
+    }
+    Ok(())
+}
+
+fn show_items(path: &std::path::Path) -> anyhow::Result<()> {
+    let connection = dbus::blocking::Connection::new_session()
+        .context("Failed to connect to the D-Bus session bus.")?;
+    let file_manager = connection.with_proxy(
+        "org.freedesktop.FileManager1",
+        "/org/freedesktop/FileManager1",
+        std::time::Duration::from_secs(2),
+    );
+    file_manager
+        .method_call::<(), _, _, _>(
+            "org.freedesktop.FileManager1",
+            "ShowItems",
+            (vec![file_uri(path)], ""),
+        )
+        .context("Failed to call ShowItems of the file manager.")
+}
+
+/// The `file://` URI of the path, with all bytes but unreserved ones and
+/// slashes percent-encoded.
+fn file_uri(path: &std::path::Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(char::from(*byte))
+            }
+            byte => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+fn xdg_open(target: impl AsRef<std::ffi::OsStr>) -> anyhow::Result<()> {
+    std::process::Command::new("xdg-open")
+        .arg(target)
+        .spawn()
+        .context("Failed to run xdg-open.")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    fn ids(actions: Vec<crate::model::Action>) -> Vec<String> {
+        actions.into_iter().map(|action| action.id).collect()
+    }
+
+    #[test]
+    fn offers_the_actions_of_the_kind() {
+        let url = crate::model::EntryKind::Url(String::from("https://docs.rs"));
+        let path = crate::model::EntryKind::Path(std::path::PathBuf::from("/home/me"));
+        let text = crate::model::EntryKind::Text(String::from("42"));
+
+        assert_eq!(ids(super::for_kind(&url)), vec![super::OPEN, super::COPY]);
+        assert_eq!(
+            ids(super::for_kind(&path)),
+            vec![super::OPEN, super::REVEAL, super::TERMINAL, super::COPY]
+        );
+        assert_eq!(ids(super::for_kind(&text)), vec![super::COPY]);
+    }
+
+    #[test]
+    fn titles_every_action() {
+        for action in [super::OPEN, super::REVEAL, super::TERMINAL, super::COPY] {
+            assert!(!super::title(action).is_empty(), "{action}");
+        }
+    }
+
+    #[test]
+    fn refuses_actions_not_offered_for_the_kind() {
+        let url = crate::model::EntryKind::Url(String::from("https://docs.rs"));
+        let text = crate::model::EntryKind::Text(String::from("42"));
+
+        assert!(super::run(&url, super::REVEAL).is_err());
+        assert!(super::run(&url, super::TERMINAL).is_err());
+        assert!(super::run(&text, super::OPEN).is_err());
+        assert!(super::run(&text, "open").is_err());
+    }
+
+    #[test]
+    fn percent_encodes_file_uris() {
+        assert_eq!(
+            super::file_uri(std::path::Path::new("/home/me/My Notes/ü#1.md")),
+            "file:///home/me/My%20Notes/%C3%BC%231.md"
+        );
+    }
+}
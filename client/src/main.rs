@@ -3,12 +3,18 @@ use iced::Application;
 
 mod cli;
 mod component;
+mod generic_actions;
 mod model;
 mod plugin;
+mod search;
 mod settings;
 
 pub fn main() -> iced::Result {
     let args = crate::cli::CliArgs::parse();
+    if args.list_plugins {
+        print!("{}", crate::plugin::registry::documentation());
+        return Ok(());
+    }
     simple_logger::init_with_level(log::Level::Info).unwrap();
     Centerpiece::run(Centerpiece::settings(args))
 }
@@ -20,15 +26,58 @@ pub enum Message {
     Event(iced::Event),
     FontLoaded(Result<(), iced::font::Error>),
     RegisterPlugin(model::Plugin),
-    UpdateEntries(String, Vec<model::Entry>),
+    /// Replaces the entries of a plugin with its results for the query of
+    /// the given generation.
+    UpdateEntries(String, u64, Vec<model::Entry>),
+    /// Adds results of a plugin that is still loading.
+    AppendEntries(String, u64, Vec<model::Entry>),
+    /// Changes the results of a plugin that updated its entries.
+    PatchEntries(String, u64, model::EntriesPatch),
+    UpdatePluginStatus(String, model::PluginStatus),
+    /// The preview of an entry as (plugin id, entry id, preview).
+    UpdatePreview(String, String, model::Preview),
     Exit,
 }
 
 struct Centerpiece {
     query: String,
-    active_entry_index: usize,
+    // The parsed query without the keyword prefix, as sent to the plugins.
+    search_query: search::query::Query,
+    selection: Option<Selection>,
+    action_menu: Option<ActionMenu>,
+    // The preview of the selected entry as (plugin id, entry id, preview).
+    preview: Option<(String, String, model::Preview)>,
+    // Keeps the first entry, which is the learned pick for the query if there
+    // is one, selected until the user moves the selection.
+    follow_first_entry: bool,
+    // Id of the plugin the query is scoped to by a keyword prefix or a `plugin:` filter.
+    scope: Option<String>,
     plugins: Vec<model::Plugin>,
+    // Script plugins found in the config directory at start as (id, path).
+    scripts: Vec<(String, std::path::PathBuf)>,
     settings: settings::Settings,
+    frecency: search::frecency::Frecency,
+    associations: search::associations::Associations,
+}
+
+/// The selected entry. Entry ids are only unique within a plugin, so the same
+/// url may be both a bookmark and a history entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Selection {
+    plugin_id: String,
+    entry_id: String,
+    // Position of the entry when it was last shown, the selection falls back
+    // to the entry taking its place once it disappears.
+    index: usize,
+}
+
+/// The actions of an entry, listed in place of the entries after pressing tab.
+struct ActionMenu {
+    plugin_id: String,
+    entry: model::Entry,
+    // The actions of the plugin followed by the generic actions for the entry kind.
+    actions: Vec<model::Action>,
+    selected_action_index: usize,
 }
 
 pub const SCROLLABLE_ID: &str = "scrollable";
@@ -41,17 +90,32 @@ impl Application for Centerpiece {
     type Flags = crate::cli::CliArgs;
 
     fn new(flags: crate::cli::CliArgs) -> (Self, iced::Command<Message>) {
-        let settings = crate::settings::Settings::try_from(flags).unwrap_or_else(|_| {
+        let mut settings = crate::settings::Settings::try_from(flags).unwrap_or_else(|_| {
             eprintln!("There is an issue with the settings, please check the configuration file.");
             std::process::exit(0);
         });
+        let scripts = crate::plugin::script::discover_scripts(&settings.plugin);
+        settings.plugin.add_scripts(&scripts);
+        search::fuzzy::Folding::share(&settings.search);
+        let frecency = search::frecency::Frecency::load();
+        frecency.share(&settings.plugin);
+        let associations = search::associations::Associations::load();
+        associations.share(&settings.plugin);
 
         (
             Self {
                 query: String::from(""),
-                active_entry_index: 0,
+                search_query: search::query::Query::default(),
+                selection: None,
+                action_menu: None,
+                preview: None,
+                follow_first_entry: true,
+                scope: None,
                 plugins: vec![],
+                scripts,
                 settings,
+                frecency,
+                associations,
             },
             iced::Command::batch(vec![
                 iced::font::load(
@@ -62,6 +126,10 @@ impl Application for Centerpiece {
                     include_bytes!("../assets/FiraCode/FiraCodeNerdFont-Light.ttf").as_slice(),
                 )
                 .map(Message::FontLoaded),
+                iced::font::load(
+                    include_bytes!("../assets/FiraCode/FiraCodeNerdFont-Bold.ttf").as_slice(),
+                )
+                .map(Message::FontLoaded),
                 iced::Command::perform(async {}, move |()| Message::Loaded),
             ]),
         )
@@ -77,6 +145,8 @@ impl Application for Centerpiece {
 
             Message::Search(input) => self.search(input),
 
+            Message::Event(event) if self.action_menu.is_some() => self.update_action_menu(event),
+
             Message::Event(event) => match event {
                 iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
                     key_code: iced::keyboard::KeyCode::Up,
@@ -106,6 +176,15 @@ impl Application for Centerpiece {
                     modifiers: iced::keyboard::Modifiers::CTRL,
                 }) => self.select_previous_plugin(),
 
+                iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                    key_code: iced::keyboard::KeyCode::Tab,
+                    ..
+                })
+                | iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                    key_code: iced::keyboard::KeyCode::Enter,
+                    modifiers: iced::keyboard::Modifiers::SHIFT,
+                }) => self.open_action_menu(),
+
                 iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
                     key_code: iced::keyboard::KeyCode::Enter,
                     ..
@@ -129,7 +208,25 @@ impl Application for Centerpiece {
 
             Message::RegisterPlugin(plugin) => self.register_plugin(plugin),
 
-            Message::UpdateEntries(plugin_id, entries) => self.update_entries(plugin_id, entries),
+            Message::UpdateEntries(plugin_id, generation, entries) => {
+                self.update_entries(plugin_id, generation, entries)
+            }
+
+            Message::AppendEntries(plugin_id, generation, entries) => {
+                self.append_entries(plugin_id, generation, entries)
+            }
+
+            Message::PatchEntries(plugin_id, generation, patch) => {
+                self.patch_entries(plugin_id, generation, patch)
+            }
+
+            Message::UpdatePluginStatus(plugin_id, status) => {
+                self.update_plugin_status(plugin_id, status)
+            }
+
+            Message::UpdatePreview(plugin_id, entry_id, preview) => {
+                self.update_preview(plugin_id, entry_id, preview)
+            }
 
             Message::Exit => iced::window::close(),
         }
@@ -153,106 +250,78 @@ impl Application for Centerpiece {
             },
         )];
 
-        if self.settings.plugin.applications.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::applications::ApplicationsPlugin,
-            >());
-        }
-
-        if self.settings.plugin.brave_bookmarks.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::brave::bookmarks::BookmarksPlugin,
-            >());
+        subscriptions.extend(crate::plugin::registry::spawn(&self.settings.plugin));
+
+        for (id, path) in self.scripts.iter() {
+            let script_settings = self.settings.plugin.script(id);
+            if script_settings.enable {
+                subscriptions.push(crate::plugin::script::spawn(
+                    id.clone(),
+                    path.clone(),
+                    script_settings,
+                ));
+            }
         }
 
-        if self.settings.plugin.brave_progressive_web_apps.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::brave::progressive_web_apps::ProgressiveWebAppsPlugin,
-            >());
+        for command_plugin_settings in self.settings.plugin.commands.iter() {
+            subscriptions.push(crate::plugin::command::spawn(
+                command_plugin_settings.clone(),
+            ));
         }
 
-        if self.settings.plugin.brave_history.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::brave::history::HistoryPlugin,
-            >());
-        }
-
-        if self.settings.plugin.clock.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::clock::ClockPlugin,
-            >());
-        }
-
-        if self.settings.plugin.git_repositories.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::git_repositories::GitRepositoriesPlugin,
-            >());
-        }
-
-        if self.settings.plugin.resource_monitor_battery.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::resource_monitor::battery::BatteryPlugin,
-            >());
-        }
-
-        if self.settings.plugin.resource_monitor_cpu.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::resource_monitor::cpu::CpuPlugin,
-            >());
-        }
-
-        if self.settings.plugin.resource_monitor_disks.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::resource_monitor::disks::DisksPlugin,
-            >());
-        }
-
-        if self.settings.plugin.resource_monitor_memory.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::resource_monitor::memory::MemoryPlugin,
-            >());
-        }
-
-        if self.settings.plugin.system.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::system::SystemPlugin,
-            >());
-        }
-
-        if self.settings.plugin.wifi.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<crate::plugin::wifi::WifiPlugin>());
-        }
-
-        if self.settings.plugin.sway_windows.enable {
-            subscriptions.push(crate::plugin::utils::spawn::<
-                crate::plugin::sway_windows::SwayWindowsPlugin,
-            >());
+        for external_plugin_settings in self.settings.plugin.external.iter() {
+            subscriptions.push(crate::plugin::external::spawn(
+                external_plugin_settings.clone(),
+            ));
         }
 
         iced::subscription::Subscription::batch(subscriptions)
     }
 
-    fn view(&self) -> iced::Element<Message> {
+    fn view(&self) -> iced::Element<'_, Message> {
         let entries = self.entries();
 
+        let results = iced::widget::scrollable(match &self.action_menu {
+            Some(action_menu) => component::action_menu::view(
+                &action_menu.entry,
+                &action_menu.actions,
+                action_menu.selected_action_index,
+            ),
+            None => self.entries_view(),
+        })
+        .id(iced::widget::scrollable::Id::new(SCROLLABLE_ID))
+        .style(iced::theme::Scrollable::Custom(Box::new(
+            ScrollableStyle {},
+        )));
+
+        let preview = self
+            .preview
+            .as_ref()
+            .filter(|(plugin_id, entry_id, _)| self.is_selected(plugin_id, entry_id))
+            .map(|(_, _, preview)| component::preview::view(preview));
+        let results: iced::Element<'_, Message> = match (self.settings.preview, preview) {
+            (settings::Preview::Right, Some(preview)) => iced::widget::row![
+                iced::widget::container(results).width(iced::Length::FillPortion(3)),
+                iced::widget::vertical_rule(1),
+                iced::widget::container(preview).width(iced::Length::FillPortion(2)),
+            ]
+            .into(),
+            (settings::Preview::Bottom, Some(preview)) => iced::widget::column![
+                iced::widget::container(results).height(iced::Length::Fill),
+                iced::widget::horizontal_rule(1),
+                iced::widget::container(preview).max_height(10. * REM),
+            ]
+            .into(),
+            _ => results.into(),
+        };
+
         iced::widget::container(iced::widget::column![
-            component::query_input::view(&self.query, !entries.is_empty()),
-            iced::widget::scrollable(iced::widget::column(
-                self.plugins
-                    .iter()
-                    .filter(|plugin| !plugin.entries.is_empty())
-                    .enumerate()
-                    .map(|(index, plugin)| component::plugin::view(
-                        plugin,
-                        index != 0,
-                        self.active_entry_id()
-                    ))
-                    .collect()
-            ))
-            .id(iced::widget::scrollable::Id::new(SCROLLABLE_ID))
-            .style(iced::theme::Scrollable::Custom(Box::new(
-                ScrollableStyle {},
-            ))),
+            component::query_input::view(
+                &self.query,
+                !entries.is_empty(),
+                self.scoped_plugin().map(|plugin| plugin.title.as_str())
+            ),
+            results,
         ])
         .style(iced::theme::Container::Custom(Box::new(
             ApplicationWrapperStyle {},
@@ -312,85 +381,397 @@ impl Centerpiece {
         }
     }
 
-    fn entries(&self) -> Vec<&model::Entry> {
-        return self
-            .plugins
+    fn plugin_weight(
+        plugin_settings: &settings::PluginSettings,
+        frecency: &search::frecency::Frecency,
+        plugin: &model::Plugin,
+    ) -> u32 {
+        let boost = match plugin_settings.frecency_enabled(&plugin.id) {
+            true => frecency.plugin_boost(&plugin.id),
+            false => 0,
+        };
+        plugin.priority + boost
+    }
+
+    /// The entries of the visible plugins in the configured layout.
+    fn entries_view(&self) -> iced::Element<'_, Message> {
+        match self.settings.layout {
+            settings::Layout::Grouped => {
+                let ranked_entries = self.ranked_entries();
+                iced::widget::column(
+                    self.visible_plugins()
+                        // Plugins without entries still show why they are loading or failed.
+                        .filter(|plugin| {
+                            !plugin.entries.is_empty()
+                                || plugin.status != model::PluginStatus::Ready
+                        })
+                        .enumerate()
+                        .map(|(index, plugin)| {
+                            let entries: Vec<&model::Entry> = ranked_entries
+                                .iter()
+                                .filter(|(entry_plugin, _)| entry_plugin.id == plugin.id)
+                                .map(|(_, entry)| *entry)
+                                .collect();
+                            component::plugin::view(
+                                plugin,
+                                &entries,
+                                index != 0,
+                                self.selection
+                                    .as_ref()
+                                    .filter(|selection| selection.plugin_id == plugin.id)
+                                    .map(|selection| &selection.entry_id),
+                            )
+                        })
+                        .collect(),
+                )
+                .into()
+            }
+            settings::Layout::Flat => {
+                let ranked_entries = self.ranked_entries();
+                let icon_column = ranked_entries.iter().any(|(_, entry)| entry.icon.is_some());
+                iced::widget::column(
+                    ranked_entries
+                        .into_iter()
+                        .map(|(plugin, entry)| {
+                            component::entry::view(
+                                entry,
+                                self.is_selected(&plugin.id, &entry.id),
+                                Some(&plugin.title),
+                                icon_column,
+                            )
+                        })
+                        .chain(
+                            self.visible_plugins()
+                                .filter(|plugin| plugin.status != model::PluginStatus::Ready)
+                                .map(component::plugin::header),
+                        )
+                        .collect(),
+                )
+                .padding(0.75 * REM)
+                .into()
+            }
+        }
+    }
+
+    fn scoped_plugin(&self) -> Option<&model::Plugin> {
+        let scope = self.scope.as_ref()?;
+        self.plugins.iter().find(|plugin| &plugin.id == scope)
+    }
+
+    /// Plugins whose entries are shown, i.e. all plugins unless the query is
+    /// scoped to one. The plugin of the learned pick comes first.
+    fn visible_plugins(&self) -> impl Iterator<Item = &model::Plugin> {
+        let learned_pick_plugin_id = self.learned_pick().and_then(|(plugin_id, entry_id)| {
+            self.plugins
+                .iter()
+                .find(|plugin| {
+                    plugin.id == plugin_id
+                        && plugin.entries.iter().any(|entry| entry.id == entry_id)
+                })
+                .map(|plugin| plugin.id.as_str())
+        });
+        let is_visible = move |plugin: &&model::Plugin| match &self.scope {
+            Some(scope) => &plugin.id == scope,
+            None => true,
+        };
+        let has_learned_pick =
+            move |plugin: &&model::Plugin| Some(plugin.id.as_str()) == learned_pick_plugin_id;
+
+        self.plugins
             .iter()
-            .flat_map(|plugin| &plugin.entries)
-            .collect();
+            .filter(move |plugin| is_visible(plugin) && has_learned_pick(plugin))
+            .chain(
+                self.plugins
+                    .iter()
+                    .filter(move |plugin| is_visible(plugin) && !has_learned_pick(plugin)),
+            )
     }
 
-    fn active_entry_id(&self) -> Option<&String> {
-        let entries = self.entries();
-        let active_entry = entries.get(self.active_entry_index);
-        match active_entry {
-            Some(entry) => Some(&entry.id),
-            None => None,
+    /// The entry activated most often for the query as (plugin id, entry id).
+    fn learned_pick(&self) -> Option<(&str, &str)> {
+        self.associations
+            .pick(&self.search_query.text)
+            .filter(|(plugin_id, _)| self.settings.plugin.frecency_enabled(plugin_id))
+    }
+
+    /// All entries together with their plugin in the order they are displayed.
+    /// The entries of the plugins keep the order of the plugin, so patches of
+    /// the plugin apply to them, and the learned pick is only moved here.
+    fn ranked_entries(&self) -> Vec<(&model::Plugin, &model::Entry)> {
+        let mut ranked_entries = match self.settings.layout {
+            settings::Layout::Grouped => self
+                .visible_plugins()
+                .flat_map(|plugin| plugin.entries.iter().map(move |entry| (plugin, entry)))
+                .collect(),
+            settings::Layout::Flat => self.entries_by_score(),
+        };
+
+        // The plugin of the learned pick comes first, so in both layouts the
+        // pick moves to the very front.
+        let learned_pick = self.learned_pick();
+        if let Some(index) = ranked_entries.iter().position(|(plugin, entry)| {
+            Some((plugin.id.as_str(), entry.id.as_str())) == learned_pick
+        }) {
+            ranked_entries[..=index].rotate_right(1);
         }
+
+        ranked_entries
+    }
+
+    /// The entries of all visible plugins, best first, for the flat layout.
+    fn entries_by_score(&self) -> Vec<(&model::Plugin, &model::Entry)> {
+        // The weight of each plugin is computed once, not on every comparison.
+        let mut weighted_entries: Vec<(u32, &model::Plugin, &model::Entry)> = self
+            .visible_plugins()
+            .flat_map(|plugin| {
+                let plugin_weight =
+                    Self::plugin_weight(&self.settings.plugin, &self.frecency, plugin);
+                plugin
+                    .entries
+                    .iter()
+                    .map(move |entry| (plugin_weight, plugin, entry))
+            })
+            .collect();
+        weighted_entries.sort_by_key(|(plugin_weight, _, entry)| {
+            (
+                entry.matched_with_typos,
+                std::cmp::Reverse(entry.score + i64::from(*plugin_weight)),
+            )
+        });
+        weighted_entries
+            .into_iter()
+            .map(|(_, plugin, entry)| (plugin, entry))
+            .collect()
+    }
+
+    fn entries(&self) -> Vec<&model::Entry> {
+        self.ranked_entries()
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    fn is_selected(&self, plugin_id: &str, entry_id: &str) -> bool {
+        self.selection.as_ref().is_some_and(|selection| {
+            selection.plugin_id == plugin_id && selection.entry_id == entry_id
+        })
+    }
+
+    /// Position of the selected entry among the displayed entries.
+    fn selected_entry_index(&self) -> Option<usize> {
+        Self::position_of_selection(&self.ranked_entries(), self.selection.as_ref())
+    }
+
+    fn position_of_selection(
+        ranked_entries: &[(&model::Plugin, &model::Entry)],
+        selection: Option<&Selection>,
+    ) -> Option<usize> {
+        let selection = selection?;
+        ranked_entries.iter().position(|(plugin, entry)| {
+            plugin.id == selection.plugin_id && entry.id == selection.entry_id
+        })
+    }
+
+    /// The index of the entry to select after the entries changed: the
+    /// selected entry where it is now, or the entry taking its place if it
+    /// disappeared, or the first entry while the selection follows it.
+    fn kept_selection_index(
+        previous_index: Option<usize>,
+        current_index: Option<usize>,
+        follow_first_entry: bool,
+        entry_count: usize,
+    ) -> Option<usize> {
+        let index = match follow_first_entry {
+            true => 0,
+            false => current_index.or(previous_index).unwrap_or(0),
+        };
+        Self::clamped_index(index, entry_count)
+    }
+
+    /// The index, or the last index if there are fewer entries. `None` if
+    /// there are no entries.
+    fn clamped_index(index: usize, entry_count: usize) -> Option<usize> {
+        entry_count
+            .checked_sub(1)
+            .map(|last_index| index.min(last_index))
+    }
+
+    fn selection_at(
+        ranked_entries: &[(&model::Plugin, &model::Entry)],
+        index: Option<usize>,
+    ) -> Option<Selection> {
+        let index = index?;
+        ranked_entries.get(index).map(|(plugin, entry)| Selection {
+            plugin_id: plugin.id.clone(),
+            entry_id: entry.id.clone(),
+            index,
+        })
     }
 
     fn search(&mut self, input: String) -> iced::Command<Message> {
+        let prefix_scope = self.plugins.iter().find_map(|plugin| {
+            let prefix = self.settings.plugin.prefix(&plugin.id)?;
+            let scoped_query = search::query::strip_scope_prefix(&input, prefix)?;
+            Some((plugin.id.clone(), scoped_query))
+        });
+
+        let mut search_query = match &prefix_scope {
+            Some((_, scoped_query)) => search::query::Query::parse(scoped_query),
+            None => search::query::Query::parse(&input),
+        };
+        search_query.generation = self.search_query.generation + 1;
+        let scope = match prefix_scope {
+            Some((scope, _)) => Some(scope),
+            None => search_query.filters.plugin.clone(),
+        };
+
+        self.scope = scope;
+        self.search_query = search_query;
         for plugin in self.plugins.iter_mut() {
-            let _ = plugin
-                .app_channel_out
-                .try_send(crate::model::PluginRequest::Search(input.clone()));
+            Self::send_search(plugin, &self.search_query, &self.scope);
         }
 
         self.query = input;
+        self.action_menu = None;
+        self.follow_first_entry = true;
         self.select_first_entry()
     }
 
+    /// Sends the search to the plugin unless it is scoped to another plugin.
+    /// A search not fitting into the full request channel is sent again once
+    /// the plugin answers, see [`Self::send_pending_search`].
+    fn send_search(
+        plugin: &mut model::Plugin,
+        search_query: &search::query::Query,
+        scope: &Option<String>,
+    ) {
+        if scope.as_ref().is_some_and(|scope| &plugin.id != scope) {
+            plugin.search_pending = false;
+            return;
+        }
+        let send_result = plugin
+            .app_channel_out
+            .try_send(crate::model::PluginRequest::Search(search_query.clone()));
+        plugin.search_pending = send_result.is_err_and(|error| error.is_full());
+    }
+
+    fn send_pending_search(&mut self, plugin_id: &str) {
+        if let Some(plugin) = self
+            .plugins
+            .iter_mut()
+            .find(|plugin| plugin.id == plugin_id && plugin.search_pending)
+        {
+            Self::send_search(plugin, &self.search_query, &self.scope);
+        }
+    }
+
     fn focus_search_input(&self) -> iced::Command<Message> {
         iced::widget::text_input::focus(iced::widget::text_input::Id::new(
             component::query_input::SEARCH_INPUT_ID,
         ))
     }
 
-    fn select_first_entry(&mut self) -> iced::Command<Message> {
-        self.active_entry_index = 0;
+    /// Selects the entry at the index, or the last entry if there are fewer entries.
+    fn select_entry(&mut self, index: usize) -> iced::Command<Message> {
+        let ranked_entries = self.ranked_entries();
+        let index = Self::clamped_index(index, ranked_entries.len());
+        let selection = Self::selection_at(&ranked_entries, index);
+        self.set_selection(selection)
+    }
+
+    fn set_selection(&mut self, selection: Option<Selection>) -> iced::Command<Message> {
+        let selected_entry_changed = self
+            .selection
+            .as_ref()
+            .map(|selection| (&selection.plugin_id, &selection.entry_id))
+            != selection
+                .as_ref()
+                .map(|selection| (&selection.plugin_id, &selection.entry_id));
+
+        self.selection = selection;
+        if selected_entry_changed {
+            self.request_preview();
+        }
         self.scroll_to_selected_entry()
     }
 
-    fn select_previous_entry(&mut self) -> iced::Command<Message> {
-        let entries = self.entries();
-        if entries.is_empty() {
-            return self.select_first_entry();
+    /// Asks the plugin of the selected entry for its preview, if the preview pane is shown.
+    fn request_preview(&mut self) {
+        self.preview = None;
+        self.send_preview_request();
+    }
+
+    /// Asks the plugin of the selected entry for its preview, unless the
+    /// plugin has no previews.
+    fn send_preview_request(&mut self) {
+        if self.settings.preview == settings::Preview::Off {
+            return;
         }
+        let Some(selection) = &self.selection else {
+            return;
+        };
+        let Some(plugin) = self
+            .plugins
+            .iter_mut()
+            .find(|plugin| plugin.id == selection.plugin_id && plugin.has_preview)
+        else {
+            return;
+        };
+        let Some(entry) = plugin
+            .entries
+            .iter()
+            .find(|entry| entry.id == selection.entry_id)
+        else {
+            return;
+        };
 
-        if self.active_entry_index == 0 {
-            self.active_entry_index = entries.len() - 1;
-            return self.scroll_to_selected_entry();
+        let preview_request = model::PluginRequest::Preview(entry.clone());
+        plugin.app_channel_out.try_send(preview_request).ok();
+    }
+
+    fn update_preview(
+        &mut self,
+        plugin_id: String,
+        entry_id: String,
+        preview: model::Preview,
+    ) -> iced::Command<Message> {
+        // The selection may have moved on while the plugin was busy.
+        if self.is_selected(&plugin_id, &entry_id) {
+            self.preview = Some((plugin_id, entry_id, preview));
         }
+        iced::Command::none()
+    }
 
-        self.active_entry_index -= 1;
-        self.scroll_to_selected_entry()
+    fn select_first_entry(&mut self) -> iced::Command<Message> {
+        self.select_entry(0)
     }
 
-    fn select_next_entry(&mut self) -> iced::Command<Message> {
-        let entries = self.entries();
-        if entries.is_empty() || self.active_entry_index == entries.len() - 1 {
-            return self.select_first_entry();
+    fn select_previous_entry(&mut self) -> iced::Command<Message> {
+        self.follow_first_entry = false;
+        match self.selected_entry_index() {
+            Some(0) => self.select_entry(self.entries().len() - 1),
+            Some(index) => self.select_entry(index - 1),
+            None => self.select_first_entry(),
         }
+    }
 
-        self.active_entry_index += 1;
-        self.scroll_to_selected_entry()
+    fn select_next_entry(&mut self) -> iced::Command<Message> {
+        self.follow_first_entry = false;
+        match self.selected_entry_index() {
+            Some(index) if index + 1 < self.entries().len() => self.select_entry(index + 1),
+            _ => self.select_first_entry(),
+        }
     }
 
     fn scroll_to_selected_entry(&self) -> iced::Command<Message> {
-        let plugin_index = match self.active_entry_id() {
-            Some(active_entry_id) => self
-                .plugins
-                .iter()
-                .position(|plugin| {
-                    plugin
-                        .entries
-                        .iter()
-                        .any(|entry| entry.id.eq(active_entry_id))
-                })
-                .unwrap_or(0) as f32,
-            None => 0.0,
+        let Some(selection) = &self.selection else {
+            return iced::Command::none();
         };
-        let entry_index = self.active_entry_index as f32;
+        let plugin_index = self
+            .visible_plugins()
+            .position(|plugin| plugin.id == selection.plugin_id)
+            .unwrap_or(0) as f32;
+        let entry_index = selection.index as f32;
 
         // 1.0 REM line height +
         // 2x0.5 REM padding +
@@ -402,106 +783,373 @@ impl Centerpiece {
         // 0.32 REM for good luck :D
         let plugin_header_height = 3.57 * crate::REM;
 
-        let offset = (plugin_index * plugin_header_height) + (entry_index * entry_height);
+        let offset = match self.settings.layout {
+            settings::Layout::Grouped => {
+                (plugin_index * plugin_header_height) + (entry_index * entry_height)
+            }
+            // The flat list has no plugin headers.
+            settings::Layout::Flat => entry_index * entry_height,
+        };
         iced::widget::scrollable::scroll_to(
             iced::widget::scrollable::Id::new(SCROLLABLE_ID),
             iced::widget::scrollable::AbsoluteOffset { x: 0.0, y: offset },
         )
     }
 
-    fn select_next_plugin(&mut self) -> iced::Command<Message> {
-        let accumulated_entries = self
-            .plugins
-            .iter()
-            .map(|plugin| plugin.entries.len())
-            .scan(0, |acc, len| {
-                let prev = *acc;
-                *acc += len;
-                Some(prev)
+    /// Indices of the first entry of every run of entries belonging to the same plugin.
+    fn plugin_run_starts(&self) -> Vec<usize> {
+        let ranked_entries = self.ranked_entries();
+        (0..ranked_entries.len())
+            .filter(|&index| {
+                index == 0 || ranked_entries[index - 1].0.id != ranked_entries[index].0.id
             })
-            .find(|&total| total > self.active_entry_index)
-            .unwrap_or(self.active_entry_index);
+            .collect()
+    }
 
-        self.active_entry_index = accumulated_entries;
-        self.scroll_to_selected_entry()
+    fn select_next_plugin(&mut self) -> iced::Command<Message> {
+        self.follow_first_entry = false;
+        let selected_entry_index = self.selected_entry_index().unwrap_or(0);
+        let next_plugin_start = self
+            .plugin_run_starts()
+            .into_iter()
+            .find(|&start| start > selected_entry_index)
+            .unwrap_or(selected_entry_index);
+
+        self.select_entry(next_plugin_start)
     }
 
     fn select_previous_plugin(&mut self) -> iced::Command<Message> {
-        if self.plugins.is_empty() || self.active_entry_index == 0 {
-            return self.select_first_entry();
-        }
+        self.follow_first_entry = false;
+        let selected_entry_index = match self.selected_entry_index() {
+            Some(0) | None => return self.select_first_entry(),
+            Some(index) => index,
+        };
 
-        let accumulated_entries = self
-            .plugins
-            .iter()
-            .map(|plugin| plugin.entries.len())
-            .scan(0, |acc, len| {
-                let prev = *acc;
-                *acc += len;
-                Some(prev)
-            })
-            .take_while(|&total| total < self.active_entry_index)
+        let previous_plugin_start = self
+            .plugin_run_starts()
+            .into_iter()
+            .take_while(|&start| start < selected_entry_index)
             .last()
             .unwrap_or(0);
 
-        self.active_entry_index = accumulated_entries;
-        self.scroll_to_selected_entry()
+        self.select_entry(previous_plugin_start)
     }
 
-    fn register_plugin(&mut self, plugin: crate::model::Plugin) -> iced::Command<Message> {
+    fn register_plugin(&mut self, mut plugin: crate::model::Plugin) -> iced::Command<Message> {
+        // Plugins registering late catch up with the query typed so far.
+        if self.search_query.generation > 0 {
+            Self::send_search(&mut plugin, &self.search_query, &self.scope);
+        }
         self.plugins.push(plugin);
-        self.plugins.sort_by(|a, b| b.priority.cmp(&a.priority));
+
+        let frecency = &self.frecency;
+        let plugin_settings = &self.settings.plugin;
+        self.plugins.sort_by_key(|plugin| {
+            std::cmp::Reverse(Self::plugin_weight(plugin_settings, frecency, plugin))
+        });
         iced::Command::none()
     }
 
     fn update_entries(
         &mut self,
         plugin_id: String,
+        generation: u64,
         entries: Vec<crate::model::Entry>,
     ) -> iced::Command<Message> {
-        let plugin = self
+        let Some(plugin_index) = self.results_target(&plugin_id, generation) else {
+            return iced::Command::none();
+        };
+
+        let plugin = &mut self.plugins[plugin_index];
+        plugin.entries = entries;
+
+        self.keep_selected_entry()
+    }
+
+    /// Adds the entries of a plugin that is still loading, keeping the
+    /// entries ordered like the results of a search.
+    fn append_entries(
+        &mut self,
+        plugin_id: String,
+        generation: u64,
+        entries: Vec<crate::model::Entry>,
+    ) -> iced::Command<Message> {
+        let Some(plugin_index) = self.results_target(&plugin_id, generation) else {
+            return iced::Command::none();
+        };
+
+        let plugin = &mut self.plugins[plugin_index];
+        plugin.entries.extend(entries);
+        plugin
+            .entries
+            .sort_by(crate::plugin::utils::compare_results);
+
+        self.keep_selected_entry()
+    }
+
+    fn patch_entries(
+        &mut self,
+        plugin_id: String,
+        generation: u64,
+        patch: crate::model::EntriesPatch,
+    ) -> iced::Command<Message> {
+        let Some(plugin_index) = self.results_target(&plugin_id, generation) else {
+            return iced::Command::none();
+        };
+
+        // The preview of a changed entry, like the usage history of a cpu
+        // core, is outdated. The shown one stays until the new one arrives.
+        let changed_selected_entry = patch
+            .changed
+            .iter()
+            .find(|entry| self.is_selected(&plugin_id, &entry.id))
+            .map(|entry| entry.id.clone());
+
+        let plugin = &mut self.plugins[plugin_index];
+        patch.apply(&mut plugin.entries);
+
+        let command = self.keep_selected_entry();
+        if changed_selected_entry.is_some_and(|entry_id| self.is_selected(&plugin_id, &entry_id)) {
+            self.send_preview_request();
+        }
+        command
+    }
+
+    /// The index of the plugin to apply results to, unless they are for an outdated query.
+    fn results_target(&mut self, plugin_id: &str, generation: u64) -> Option<usize> {
+        self.send_pending_search(plugin_id);
+        if generation < self.search_query.generation {
+            log::debug!(
+                "Dropping results of plugin {:?} for outdated query {}",
+                plugin_id,
+                generation
+            );
+            return None;
+        }
+
+        let plugin_index = self
             .plugins
-            .iter_mut()
-            .find(|plugin| plugin.id == plugin_id);
-        if plugin.is_none() {
-            println!(
-                "Appending entry failed. Could not find plugin with id {:?}",
+            .iter()
+            .position(|plugin| plugin.id == plugin_id);
+        if plugin_index.is_none() {
+            log::warn!(
+                "Updating entries failed. Could not find plugin with id {:?}",
                 plugin_id
             );
-            return iced::Command::none();
         }
+        plugin_index
+    }
 
-        let plugin = plugin.unwrap();
-        plugin.entries = entries;
-        iced::Command::none()
+    /// Keeps the selected entry selected when results change. A selected entry
+    /// that disappeared is replaced by the entry taking its place. Until the user
+    /// moves the selection, it follows the learned pick or the first entry.
+    fn keep_selected_entry(&mut self) -> iced::Command<Message> {
+        let ranked_entries = self.ranked_entries();
+        let index = Self::kept_selection_index(
+            self.selection.as_ref().map(|selection| selection.index),
+            Self::position_of_selection(&ranked_entries, self.selection.as_ref()),
+            self.follow_first_entry,
+            ranked_entries.len(),
+        );
+        let selection = Self::selection_at(&ranked_entries, index);
+
+        match selection == self.selection {
+            true => iced::Command::none(),
+            false => self.set_selection(selection),
+        }
+    }
+
+    fn update_plugin_status(
+        &mut self,
+        plugin_id: String,
+        status: crate::model::PluginStatus,
+    ) -> iced::Command<Message> {
+        self.send_pending_search(&plugin_id);
+        match self
+            .plugins
+            .iter_mut()
+            .find(|plugin| plugin.id == plugin_id)
+        {
+            Some(plugin) => {
+                // Failed plugins are restarted and send their entries again.
+                if let crate::model::PluginStatus::Error(_) = status {
+                    plugin.entries.clear();
+                }
+                plugin.status = status;
+            }
+            None => log::warn!(
+                "Updating status failed. Could not find plugin with id {:?}",
+                plugin_id
+            ),
+        }
+        self.keep_selected_entry()
+    }
+
+    fn selected_entry(&self) -> Option<(&model::Plugin, &model::Entry)> {
+        let selection = self.selection.as_ref()?;
+        let plugin = self
+            .plugins
+            .iter()
+            .find(|plugin| plugin.id == selection.plugin_id)?;
+        let entry = plugin
+            .entries
+            .iter()
+            .find(|entry| entry.id == selection.entry_id)?;
+        Some((plugin, entry))
     }
 
     fn activate_selected_entry(&mut self) -> Option<iced::Command<Message>> {
-        let active_entry_id = self.active_entry_id()?.clone();
+        let (plugin, entry) = self.selected_entry()?;
+        let plugin_id = plugin.id.clone();
+        let entry = entry.clone();
+        let action = String::from(entry.primary_action());
+        self.activate_entry(&plugin_id, entry, action)
+    }
 
-        let entry = self
-            .entries()
-            .into_iter()
-            .find(|entry| entry.id == *active_entry_id)?
-            .clone();
+    fn activate_entry(
+        &mut self,
+        plugin_id: &str,
+        entry: model::Entry,
+        action: String,
+    ) -> Option<iced::Command<Message>> {
+        let plugin = self
+            .plugins
+            .iter_mut()
+            .find(|plugin| plugin.id == plugin_id)?;
+
+        if self.settings.plugin.frecency_enabled(&plugin.id) {
+            self.frecency.record(&plugin.id, &entry.id);
+            if let Err(error) = self.frecency.save() {
+                log::warn!("Failed to save frecency store: {:?}", error);
+            }
+
+            if !self.search_query.text.is_empty() {
+                self.associations
+                    .record(&self.search_query.text, &plugin.id, &entry.id);
+                if let Err(error) = self.associations.save() {
+                    log::warn!("Failed to save associations store: {:?}", error);
+                }
+            }
+        }
 
-        let plugin = self.plugins.iter_mut().find(|plugin| {
-            plugin
-                .entries
+        if let Some(kind) = entry.kind.as_ref().filter(|kind| {
+            crate::generic_actions::for_kind(kind)
                 .iter()
-                .any(|entry| entry.id == *active_entry_id)
-        })?;
+                .any(|generic_action| generic_action.id == action)
+        }) {
+            return match crate::generic_actions::run(kind, &action) {
+                Ok(()) => Some(iced::window::close()),
+                Err(error) => {
+                    log::error!(target: plugin_id, "{:?}", error);
+                    Some(iced::Command::none())
+                }
+            };
+        }
 
         plugin
             .app_channel_out
-            .try_send(model::PluginRequest::Activate(entry))
+            .try_send(model::PluginRequest::Activate(entry, action))
             .ok();
         Some(iced::Command::none())
     }
+
+    fn open_action_menu(&mut self) -> iced::Command<Message> {
+        if let Some((plugin, entry)) = self.selected_entry() {
+            let mut actions = entry.actions.clone();
+            if let Some(kind) = &entry.kind {
+                actions.extend(crate::generic_actions::for_kind(kind));
+            }
+
+            if !actions.is_empty() {
+                self.action_menu = Some(ActionMenu {
+                    plugin_id: plugin.id.clone(),
+                    entry: entry.clone(),
+                    actions,
+                    selected_action_index: 0,
+                });
+            }
+        }
+        iced::Command::none()
+    }
+
+    /// Handles the keys while the action menu is open. Tab, shift+enter and escape close it.
+    fn update_action_menu(&mut self, event: iced::Event) -> iced::Command<Message> {
+        let Some(action_menu) = self.action_menu.as_mut() else {
+            return iced::Command::none();
+        };
+        let actions_count = action_menu.actions.len();
+
+        match event {
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::Up,
+                ..
+            })
+            | iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::K,
+                modifiers: iced::keyboard::Modifiers::CTRL,
+            }) => {
+                action_menu.selected_action_index =
+                    (action_menu.selected_action_index + actions_count - 1) % actions_count;
+                iced::Command::none()
+            }
+
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::Down,
+                ..
+            })
+            | iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::J,
+                modifiers: iced::keyboard::Modifiers::CTRL,
+            }) => {
+                action_menu.selected_action_index =
+                    (action_menu.selected_action_index + 1) % actions_count;
+                iced::Command::none()
+            }
+
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::Enter,
+                modifiers,
+            }) if !modifiers.shift() => {
+                let Some(action_menu) = self.action_menu.take() else {
+                    return iced::Command::none();
+                };
+                let action = action_menu.actions[action_menu.selected_action_index]
+                    .id
+                    .clone();
+                self.activate_entry(&action_menu.plugin_id, action_menu.entry, action)
+                    .unwrap_or(iced::Command::none())
+            }
+
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::Tab,
+                ..
+            })
+            | iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                key_code: iced::keyboard::KeyCode::Enter,
+                ..
+            })
+            | iced::Event::Keyboard(iced::keyboard::Event::KeyReleased {
+                key_code: iced::keyboard::KeyCode::Escape,
+                ..
+            }) => {
+                self.action_menu = None;
+                iced::Command::none()
+            }
+
+            iced::Event::Mouse(iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left)) => {
+                self.focus_search_input()
+            }
+
+            _ => iced::Command::none(),
+        }
+    }
 }
 
 pub const REM: f32 = 14.0;
 
+/// The color of all text, statuses and hints are shown dimmed.
+pub const TEXT_COLOR: iced::Color = iced::Color::WHITE;
+
 struct SandboxStyle {}
 impl iced::application::StyleSheet for SandboxStyle {
     type Style = iced::Theme;
@@ -509,7 +1157,7 @@ impl iced::application::StyleSheet for SandboxStyle {
     fn appearance(&self, _style: &Self::Style) -> iced::application::Appearance {
         iced::application::Appearance {
             background_color: iced::Color::TRANSPARENT,
-            text_color: iced::Color::WHITE,
+            text_color: TEXT_COLOR,
         }
     }
 }
@@ -567,3 +1215,419 @@ impl iced::widget::scrollable::StyleSheet for ScrollableStyle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Centerpiece;
+
+    fn centerpiece() -> Centerpiece {
+        Centerpiece {
+            query: String::new(),
+            search_query: crate::search::query::Query::default(),
+            selection: None,
+            action_menu: None,
+            preview: None,
+            follow_first_entry: true,
+            scope: None,
+            plugins: vec![],
+            scripts: vec![],
+            settings: crate::settings::Settings::default(),
+            frecency: crate::search::frecency::Frecency::default(),
+            associations: crate::search::associations::Associations::default(),
+        }
+    }
+
+    /// Adds a plugin whose request channel holds a single request.
+    fn add_plugin(
+        centerpiece: &mut Centerpiece,
+        id: &str,
+    ) -> iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest> {
+        let (app_channel_out, plugin_channel_in) = iced::futures::channel::mpsc::channel(0);
+        centerpiece.plugins.push(crate::model::Plugin {
+            id: String::from(id),
+            priority: 0,
+            title: String::from(id),
+            entries: vec![],
+            status: crate::model::PluginStatus::default(),
+            search_pending: false,
+            has_preview: false,
+            app_channel_out,
+        });
+        plugin_channel_in
+    }
+
+    fn received_search(
+        plugin_channel_in: &mut iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+    ) -> Option<String> {
+        match plugin_channel_in.try_next() {
+            Ok(Some(crate::model::PluginRequest::Search(query))) => Some(query.text),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn drops_results_for_outdated_queries() {
+        let mut centerpiece = centerpiece();
+        let _plugin_channel_in = add_plugin(&mut centerpiece, "first");
+        let _plugin_channel_in = add_plugin(&mut centerpiece, "second");
+        centerpiece.search_query.generation = 3;
+
+        assert_eq!(centerpiece.results_target("second", 2), None);
+        assert_eq!(centerpiece.results_target("second", 3), Some(1));
+        assert_eq!(centerpiece.results_target("unknown", 3), None);
+    }
+
+    #[test]
+    fn resends_searches_once_the_plugin_answers() {
+        let mut centerpiece = centerpiece();
+        let mut plugin_channel_in = add_plugin(&mut centerpiece, "plugin");
+
+        let _ = centerpiece.search(String::from("fi"));
+        let _ = centerpiece.search(String::from("fire"));
+        assert!(centerpiece.plugins[0].search_pending);
+        assert_eq!(
+            received_search(&mut plugin_channel_in).as_deref(),
+            Some("fi")
+        );
+
+        // Results for the first search are outdated, but the plugin is ready
+        // for the latest search.
+        assert_eq!(centerpiece.results_target("plugin", 1), None);
+        assert!(!centerpiece.plugins[0].search_pending);
+        assert_eq!(
+            received_search(&mut plugin_channel_in).as_deref(),
+            Some("fire")
+        );
+    }
+
+    #[test]
+    fn keeps_the_selected_entry_where_it_moved() {
+        assert_eq!(
+            Centerpiece::kept_selection_index(Some(2), Some(5), false, 8),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn selects_the_entry_taking_the_place_of_a_disappeared_one() {
+        assert_eq!(
+            Centerpiece::kept_selection_index(Some(2), None, false, 8),
+            Some(2)
+        );
+        assert_eq!(
+            Centerpiece::kept_selection_index(Some(7), None, false, 3),
+            Some(2)
+        );
+        assert_eq!(
+            Centerpiece::kept_selection_index(None, None, false, 3),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn follows_the_first_entry_until_the_selection_moves() {
+        assert_eq!(
+            Centerpiece::kept_selection_index(Some(2), Some(5), true, 8),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn selects_nothing_without_entries() {
+        assert_eq!(
+            Centerpiece::kept_selection_index(Some(2), None, false, 0),
+            None
+        );
+        assert_eq!(Centerpiece::clamped_index(0, 0), None);
+        assert_eq!(Centerpiece::clamped_index(4, 2), Some(1));
+    }
+
+    fn key_pressed(
+        key_code: iced::keyboard::KeyCode,
+        modifiers: iced::keyboard::Modifiers,
+    ) -> iced::Event {
+        iced::Event::Keyboard(iced::keyboard::Event::KeyPressed {
+            key_code,
+            modifiers,
+        })
+    }
+
+    /// Opens the action menu of a bookmark offering two actions of its own.
+    fn open_bookmark_action_menu() -> (
+        Centerpiece,
+        iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+    ) {
+        let mut centerpiece = centerpiece();
+        let plugin_channel_in = add_plugin(&mut centerpiece, "bookmarks");
+        centerpiece.plugins[0].entries.push(crate::model::Entry {
+            id: String::from("https://rhai.rs"),
+            title: String::from("Rhai"),
+            actions: vec![
+                crate::model::Action::new("open", "open"),
+                crate::model::Action::new("open-in-app-mode", "open in app mode"),
+            ],
+            meta: String::new(),
+            command: None,
+            kind: Some(crate::model::EntryKind::Url(String::from(
+                "https://rhai.rs",
+            ))),
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        });
+        centerpiece.selection = Some(super::Selection {
+            plugin_id: String::from("bookmarks"),
+            entry_id: String::from("https://rhai.rs"),
+            index: 0,
+        });
+        let _ = centerpiece.open_action_menu();
+        (centerpiece, plugin_channel_in)
+    }
+
+    fn selected_action(centerpiece: &Centerpiece) -> Option<&str> {
+        let action_menu = centerpiece.action_menu.as_ref()?;
+        Some(&action_menu.actions[action_menu.selected_action_index].id)
+    }
+
+    #[test]
+    fn lists_the_generic_actions_after_the_actions_of_the_plugin() {
+        let (centerpiece, _plugin_channel_in) = open_bookmark_action_menu();
+        let actions: Vec<&str> = centerpiece
+            .action_menu
+            .as_ref()
+            .unwrap()
+            .actions
+            .iter()
+            .map(|action| action.id.as_str())
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                "open",
+                "open-in-app-mode",
+                crate::generic_actions::OPEN,
+                crate::generic_actions::COPY
+            ]
+        );
+    }
+
+    #[test]
+    fn moves_through_the_actions_and_wraps_around() {
+        let (mut centerpiece, _plugin_channel_in) = open_bookmark_action_menu();
+        let none = iced::keyboard::Modifiers::empty();
+
+        let _ = centerpiece.update_action_menu(key_pressed(iced::keyboard::KeyCode::Up, none));
+        assert_eq!(
+            selected_action(&centerpiece),
+            Some(crate::generic_actions::COPY)
+        );
+        let _ = centerpiece.update_action_menu(key_pressed(iced::keyboard::KeyCode::Down, none));
+        assert_eq!(selected_action(&centerpiece), Some("open"));
+        let _ = centerpiece.update_action_menu(key_pressed(
+            iced::keyboard::KeyCode::J,
+            iced::keyboard::Modifiers::CTRL,
+        ));
+        assert_eq!(selected_action(&centerpiece), Some("open-in-app-mode"));
+        let _ = centerpiece.update_action_menu(key_pressed(
+            iced::keyboard::KeyCode::K,
+            iced::keyboard::Modifiers::CTRL,
+        ));
+        assert_eq!(selected_action(&centerpiece), Some("open"));
+    }
+
+    #[test]
+    fn runs_the_selected_action_by_its_id_on_enter() {
+        let (mut centerpiece, mut plugin_channel_in) = open_bookmark_action_menu();
+        let none = iced::keyboard::Modifiers::empty();
+
+        let _ = centerpiece.update_action_menu(key_pressed(iced::keyboard::KeyCode::Down, none));
+        let _ = centerpiece.update_action_menu(key_pressed(iced::keyboard::KeyCode::Enter, none));
+
+        assert!(centerpiece.action_menu.is_none());
+        match plugin_channel_in.try_next() {
+            Ok(Some(crate::model::PluginRequest::Activate(entry, action))) => {
+                assert_eq!(entry.id, "https://rhai.rs");
+                assert_eq!(action, "open-in-app-mode");
+            }
+            _ => panic!("The plugin was not asked to run the action."),
+        }
+    }
+
+    #[test]
+    fn closes_the_action_menu_without_running_an_action() {
+        for key_code in [iced::keyboard::KeyCode::Tab, iced::keyboard::KeyCode::Enter] {
+            let (mut centerpiece, mut plugin_channel_in) = open_bookmark_action_menu();
+
+            let _ = centerpiece
+                .update_action_menu(key_pressed(key_code, iced::keyboard::Modifiers::SHIFT));
+
+            assert!(centerpiece.action_menu.is_none());
+            assert!(plugin_channel_in.try_next().is_err());
+        }
+    }
+
+    fn entry(id: &str, title: &str) -> crate::model::Entry {
+        crate::model::Entry {
+            id: String::from(id),
+            title: String::from(title),
+            actions: vec![],
+            meta: String::new(),
+            command: None,
+            kind: None,
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        }
+    }
+
+    fn select(centerpiece: &mut Centerpiece, plugin_id: &str, entry_id: &str) {
+        centerpiece.selection = Some(super::Selection {
+            plugin_id: String::from(plugin_id),
+            entry_id: String::from(entry_id),
+            index: 0,
+        });
+    }
+
+    fn received_preview(
+        plugin_channel_in: &mut iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+    ) -> Option<String> {
+        match plugin_channel_in.try_next() {
+            Ok(Some(crate::model::PluginRequest::Preview(entry))) => Some(entry.id),
+            _ => None,
+        }
+    }
+
+    /// A centerpiece showing previews with a cpu plugin selected, which has
+    /// previews, and an apps plugin, which has none.
+    fn centerpiece_with_previews() -> (
+        Centerpiece,
+        iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+        iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+    ) {
+        let mut centerpiece = centerpiece();
+        centerpiece.settings.preview = crate::settings::Preview::Right;
+        let cpu_channel_in = add_plugin(&mut centerpiece, "cpu");
+        centerpiece.plugins[0].has_preview = true;
+        centerpiece.plugins[0].entries = vec![entry("cpu0", "cpu0: 5%"), entry("cpu1", "cpu1: 7%")];
+        let apps_channel_in = add_plugin(&mut centerpiece, "apps");
+        centerpiece.plugins[1].entries = vec![entry("firefox", "Firefox")];
+        select(&mut centerpiece, "cpu", "cpu0");
+        (centerpiece, cpu_channel_in, apps_channel_in)
+    }
+
+    #[test]
+    fn asks_only_plugins_with_previews_for_previews() {
+        let (mut centerpiece, mut cpu_channel_in, mut apps_channel_in) =
+            centerpiece_with_previews();
+
+        centerpiece.request_preview();
+        assert_eq!(
+            received_preview(&mut cpu_channel_in).as_deref(),
+            Some("cpu0")
+        );
+
+        select(&mut centerpiece, "apps", "firefox");
+        centerpiece.request_preview();
+        assert!(apps_channel_in.try_next().is_err());
+    }
+
+    #[test]
+    fn asks_again_for_the_preview_of_a_changed_entry() {
+        let (mut centerpiece, mut cpu_channel_in, _apps_channel_in) = centerpiece_with_previews();
+        let preview = crate::model::Preview {
+            fields: vec![],
+            text: String::from("▁▂"),
+        };
+        centerpiece.preview = Some((String::from("cpu"), String::from("cpu0"), preview));
+
+        let _ = centerpiece.patch_entries(
+            String::from("cpu"),
+            0,
+            crate::model::EntriesPatch {
+                removed: vec![],
+                changed: vec![entry("cpu1", "cpu1: 9%")],
+                added: vec![],
+            },
+        );
+        assert!(cpu_channel_in.try_next().is_err());
+
+        let _ = centerpiece.patch_entries(
+            String::from("cpu"),
+            0,
+            crate::model::EntriesPatch {
+                removed: vec![],
+                changed: vec![entry("cpu0", "cpu0: 12%")],
+                added: vec![],
+            },
+        );
+        assert_eq!(
+            received_preview(&mut cpu_channel_in).as_deref(),
+            Some("cpu0")
+        );
+        // The previous preview is shown until the new one arrives.
+        assert!(centerpiece.preview.is_some());
+    }
+
+    fn ranked_entry_ids(centerpiece: &Centerpiece) -> Vec<&str> {
+        centerpiece
+            .ranked_entries()
+            .into_iter()
+            .map(|(_, entry)| entry.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn patches_the_order_of_the_plugin_while_showing_the_learned_pick_first() {
+        for layout in [
+            crate::settings::Layout::Grouped,
+            crate::settings::Layout::Flat,
+        ] {
+            let mut centerpiece = centerpiece();
+            centerpiece.settings.layout = layout;
+            let _plugin_channel_in = add_plugin(&mut centerpiece, "applications");
+            centerpiece.plugins[0].entries = vec![
+                entry("firefox", "Firefox"),
+                entry("telegram", "Telegram"),
+                entry("thunderbird", "Thunderbird"),
+            ];
+            centerpiece
+                .associations
+                .record("te", "applications", "telegram");
+            centerpiece
+                .associations
+                .record("te", "applications", "telegram");
+            centerpiece.search_query = crate::search::query::Query::parse("te");
+            assert_eq!(
+                ranked_entry_ids(&centerpiece),
+                vec!["telegram", "firefox", "thunderbird"]
+            );
+
+            // The index of the added entry is one in the order of the plugin.
+            let _ = centerpiece.patch_entries(
+                String::from("applications"),
+                0,
+                crate::model::EntriesPatch {
+                    removed: vec![],
+                    changed: vec![],
+                    added: vec![(1, entry("terminal", "Terminal"))],
+                },
+            );
+
+            let plugin_entry_ids: Vec<&str> = centerpiece.plugins[0]
+                .entries
+                .iter()
+                .map(|entry| entry.id.as_str())
+                .collect();
+            assert_eq!(
+                plugin_entry_ids,
+                vec!["firefox", "terminal", "telegram", "thunderbird"]
+            );
+            assert_eq!(
+                ranked_entry_ids(&centerpiece),
+                vec!["telegram", "firefox", "terminal", "thunderbird"]
+            );
+        }
+    }
+}
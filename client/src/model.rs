@@ -1,23 +1,102 @@
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PluginStatus {
+    /// The plugin is collecting its entries.
+    #[default]
+    Loading,
+    Ready,
+    /// The plugin failed and is restarted after a backoff.
+    Error(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct Plugin {
     pub id: String,
     pub priority: u32,
     pub title: String,
     pub entries: Vec<Entry>,
+    pub status: PluginStatus,
+    /// The latest search did not fit into the full request channel and is
+    /// sent once the plugin answers.
+    pub search_pending: bool,
+    /// Whether the plugin shows previews. Only such plugins are asked for them.
+    pub has_preview: bool,
     pub app_channel_out: iced::futures::channel::mpsc::Sender<PluginRequest>,
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd)]
+#[derive(Debug, Clone)]
 pub struct Entry {
     pub id: String,
     pub title: String,
-    pub action: String,
+    /// The actions offered for the entry. The first one runs on enter, all of
+    /// them are listed in the action menu.
+    pub actions: Vec<Action>,
     pub meta: String,
     pub command: Option<Vec<String>>,
+    /// What the entry stands for, used to offer generic actions like copying it.
+    pub kind: Option<EntryKind>,
+    /// An SVG or PNG file shown next to the title.
+    pub icon: Option<std::path::PathBuf>,
+    /// Character (not byte) ranges of the title that matched the current query.
+    pub highlights: Vec<std::ops::Range<usize>>,
+    /// Ranking score of the entry for the current query, higher is better.
+    pub score: i64,
+    /// Whether the entry only matches the current query with typos. Such
+    /// entries rank below all entries matching without, whatever their score.
+    pub matched_with_typos: bool,
+}
+
+/// An action offered for an entry. Plugins are asked to run it by its id, the
+/// title is only shown in the action menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action {
+    pub id: String,
+    pub title: String,
+}
+
+impl Action {
+    pub fn new(id: &str, title: &str) -> Self {
+        Self {
+            id: String::from(id),
+            title: String::from(title),
+        }
+    }
+}
+
+impl Entry {
+    /// The id of the action run on enter, empty if the entry offers no actions.
+    pub fn primary_action(&self) -> &str {
+        self.actions.first().map_or("", |action| action.id.as_str())
+    }
+
+    /// Compares all fields, unlike `==` which only compares the ids.
+    pub fn same_content(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.title == other.title
+            && self.actions == other.actions
+            && self.meta == other.meta
+            && self.command == other.command
+            && self.kind == other.kind
+            && self.icon == other.icon
+            && self.highlights == other.highlights
+            && self.score == other.score
+            && self.matched_with_typos == other.matched_with_typos
+    }
 }
 
 impl Eq for Entry {}
 
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl PartialEq for Entry {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -30,8 +109,212 @@ impl std::hash::Hash for Entry {
     }
 }
 
+/// What an entry stands for, no matter which plugin it comes from.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Url(String),
+    Path(std::path::PathBuf),
+    Command(Vec<String>),
+    Text(String),
+}
+
+impl EntryKind {
+    /// The names of the kinds, as used by the `kind:` filter.
+    pub const NAMES: [&'static str; 4] = ["url", "path", "command", "text"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntryKind::Url(_) => "url",
+            EntryKind::Path(_) => "path",
+            EntryKind::Command(_) => "command",
+            EntryKind::Text(_) => "text",
+        }
+    }
+
+    /// The payload as plain text, e.g. to copy it.
+    pub fn text(&self) -> String {
+        match self {
+            EntryKind::Url(url) => url.clone(),
+            EntryKind::Path(path) => path.to_string_lossy().into_owned(),
+            EntryKind::Command(command) => command.join(" "),
+            EntryKind::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// Details about an entry, shown in the preview pane while it is selected.
+#[derive(Debug, Clone, Default)]
+pub struct Preview {
+    /// Labeled values, like the url and the last visit of a history entry.
+    pub fields: Vec<(String, String)>,
+    /// Text shown below the fields, like the changes in a git repository.
+    pub text: String,
+}
+
+/// The changes between two result lists of a plugin, keyed by entry id.
+#[derive(Debug, Clone, Default)]
+pub struct EntriesPatch {
+    /// Ids of the entries to remove.
+    pub removed: Vec<String>,
+    /// Entries replacing the entries with the same id.
+    pub changed: Vec<Entry>,
+    /// New entries with their index in the patched list, ascending.
+    pub added: Vec<(usize, Entry)>,
+}
+
+impl EntriesPatch {
+    /// Returns `None` if the entries kept were reordered or ids are not
+    /// unique, in which case the whole list has to be sent.
+    pub fn between(old_entries: &[Entry], new_entries: &[Entry]) -> Option<Self> {
+        let old_by_id: std::collections::HashMap<&str, &Entry> = old_entries
+            .iter()
+            .map(|entry| (entry.id.as_str(), entry))
+            .collect();
+        let new_ids: std::collections::HashSet<&str> =
+            new_entries.iter().map(|entry| entry.id.as_str()).collect();
+        if old_by_id.len() != old_entries.len() || new_ids.len() != new_entries.len() {
+            return None;
+        }
+
+        let kept_in_old_order = old_entries
+            .iter()
+            .filter(|entry| new_ids.contains(entry.id.as_str()));
+        let kept_in_new_order = new_entries
+            .iter()
+            .filter(|entry| old_by_id.contains_key(entry.id.as_str()));
+        if !kept_in_old_order
+            .map(|entry| &entry.id)
+            .eq(kept_in_new_order.map(|entry| &entry.id))
+        {
+            return None;
+        }
+
+        let mut patch = Self {
+            removed: old_entries
+                .iter()
+                .filter(|entry| !new_ids.contains(entry.id.as_str()))
+                .map(|entry| entry.id.clone())
+                .collect(),
+            ..Self::default()
+        };
+        for (index, entry) in new_entries.iter().enumerate() {
+            match old_by_id.get(entry.id.as_str()) {
+                Some(old_entry) if old_entry.same_content(entry) => {}
+                Some(_) => patch.changed.push(entry.clone()),
+                None => patch.added.push((index, entry.clone())),
+            }
+        }
+
+        Some(patch)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.changed.is_empty() && self.added.is_empty()
+    }
+
+    /// Patches the entries in place.
+    pub fn apply(self, entries: &mut Vec<Entry>) {
+        if !self.removed.is_empty() {
+            let removed: std::collections::HashSet<String> = self.removed.into_iter().collect();
+            entries.retain(|entry| !removed.contains(&entry.id));
+        }
+        for changed_entry in self.changed {
+            if let Some(entry) = entries
+                .iter_mut()
+                .find(|entry| entry.id == changed_entry.id)
+            {
+                *entry = changed_entry;
+            }
+        }
+        for (index, added_entry) in self.added {
+            entries.insert(index.min(entries.len()), added_entry);
+        }
+    }
+}
+
 pub enum PluginRequest {
-    Search(String),
+    Search(crate::search::query::Query),
     Timeout,
-    Activate(Entry),
+    /// Runs the action with the id, one of the entry's actions, on the entry.
+    Activate(Entry, String),
+    /// Asks for the preview of the selected entry, only sent while the
+    /// preview pane is shown.
+    Preview(Entry),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntriesPatch, Entry};
+
+    fn entry(id: &str, title: &str) -> Entry {
+        Entry {
+            id: String::from(id),
+            title: String::from(title),
+            actions: vec![],
+            meta: String::new(),
+            command: None,
+            kind: None,
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        }
+    }
+
+    fn titles(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.title.as_str()).collect()
+    }
+
+    #[test]
+    fn sends_only_changed_entries() {
+        let old_entries = vec![entry("time", "12:00:00"), entry("date", "Monday")];
+        let new_entries = vec![entry("time", "12:00:01"), entry("date", "Monday")];
+
+        let patch = EntriesPatch::between(&old_entries, &new_entries).unwrap();
+        assert!(patch.removed.is_empty());
+        assert!(patch.added.is_empty());
+        assert_eq!(titles(&patch.changed), vec!["12:00:01"]);
+
+        let mut entries = old_entries;
+        patch.apply(&mut entries);
+        assert_eq!(titles(&entries), vec!["12:00:01", "Monday"]);
+    }
+
+    #[test]
+    fn adds_and_removes_entries_in_place() {
+        let old_entries = vec![entry("a", "A"), entry("b", "B"), entry("c", "C")];
+        let new_entries = vec![
+            entry("x", "X"),
+            entry("a", "A"),
+            entry("c", "C"),
+            entry("y", "Y"),
+        ];
+
+        let patch = EntriesPatch::between(&old_entries, &new_entries).unwrap();
+        assert_eq!(patch.removed, vec!["b"]);
+        assert!(patch.changed.is_empty());
+
+        let mut entries = old_entries;
+        patch.apply(&mut entries);
+        assert_eq!(titles(&entries), vec!["X", "A", "C", "Y"]);
+    }
+
+    #[test]
+    fn is_empty_without_changes() {
+        let entries = vec![entry("a", "A"), entry("b", "B")];
+        assert!(EntriesPatch::between(&entries, &entries)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn gives_up_on_reordered_or_duplicate_entries() {
+        let old_entries = vec![entry("a", "A"), entry("b", "B")];
+        let reordered_entries = vec![entry("b", "B"), entry("a", "A")];
+        assert!(EntriesPatch::between(&old_entries, &reordered_entries).is_none());
+
+        let duplicate_entries = vec![entry("a", "A"), entry("a", "A2")];
+        assert!(EntriesPatch::between(&old_entries, &duplicate_entries).is_none());
+    }
 }
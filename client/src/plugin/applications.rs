@@ -3,9 +3,68 @@ use anyhow::Context;
 
 pub struct ApplicationsPlugin {
     entries: Vec<crate::model::Entry>,
+    // The details of the applications by entry id.
+    details: std::collections::HashMap<String, ApplicationDetails>,
 }
 
-fn read_desktop_entry(path: &std::path::PathBuf) -> anyhow::Result<crate::model::Entry> {
+/// What is known about an application besides its entry.
+struct ApplicationDetails {
+    comment: Option<String>,
+    exec: String,
+    desktop_actions: Vec<DesktopAction>,
+}
+
+/// An additional action declared by a desktop entry, like opening a private window.
+#[derive(Debug, PartialEq)]
+struct DesktopAction {
+    /// The key of the action in the desktop entry, unique within the entry.
+    key: String,
+    name: String,
+    command: Vec<String>,
+}
+
+const OPEN: &str = "open";
+const OPEN_IN_TERMINAL: &str = "open-in-terminal";
+/// Prefix of the action ids of desktop actions, followed by the action key.
+/// Keeps desktop actions apart from the actions above, whatever their names.
+const DESKTOP_ACTION_PREFIX: &str = "desktop-action:";
+
+/// Splits the `Exec` field into a command, dropping field codes like `%u`.
+fn parse_exec(exec: &str) -> Vec<String> {
+    exec.split_ascii_whitespace()
+        .filter_map(|s| {
+            if s.starts_with('%') {
+                None
+            } else {
+                Some(String::from(s))
+            }
+        })
+        .collect()
+}
+
+fn read_desktop_actions(
+    desktop_entry: &freedesktop_desktop_entry::DesktopEntry,
+    locale: &str,
+) -> Vec<DesktopAction> {
+    desktop_entry
+        .actions()
+        .unwrap_or("")
+        .split(';')
+        .filter(|action| !action.is_empty())
+        .filter_map(|action| {
+            Some(DesktopAction {
+                key: String::from(action),
+                name: desktop_entry.action_name(action, Some(locale))?.to_string(),
+                command: parse_exec(desktop_entry.action_exec(action)?),
+            })
+        })
+        .collect()
+}
+
+fn read_desktop_entry(
+    path: &std::path::PathBuf,
+    icons: &mut crate::plugin::icons::IconLookup,
+) -> anyhow::Result<(crate::model::Entry, ApplicationDetails)> {
     let pathstr = path.to_str().unwrap_or("");
     let bytes = std::fs::read_to_string(path)?;
     let desktop_entry = freedesktop_desktop_entry::DesktopEntry::decode(path, &bytes)?;
@@ -26,21 +85,12 @@ fn read_desktop_entry(path: &std::path::PathBuf) -> anyhow::Result<crate::model:
         ))?
         .to_string();
 
-    let cmd = desktop_entry
-        .exec()
-        .context(format!(
-            "Desktop entry at path '{}' is missing the 'exec' field.",
-            pathstr
-        ))?
-        .split_ascii_whitespace()
-        .filter_map(|s| {
-            if s.starts_with('%') {
-                None
-            } else {
-                Some(String::from(s))
-            }
-        })
-        .collect();
+    let exec = desktop_entry.exec().context(format!(
+        "Desktop entry at path '{}' is missing the 'exec' field.",
+        pathstr
+    ))?;
+    let cmd = parse_exec(exec);
+    let desktop_actions = read_desktop_actions(&desktop_entry, &locale);
 
     let mut meta = desktop_entry
         .keywords()
@@ -48,13 +98,59 @@ fn read_desktop_entry(path: &std::path::PathBuf) -> anyhow::Result<crate::model:
         .replace(';', " ");
     meta.push_str(" Applications Apps");
 
-    Ok(crate::model::Entry {
+    let actions = [
+        crate::model::Action::new(OPEN, "open"),
+        crate::model::Action::new(OPEN_IN_TERMINAL, "open in terminal"),
+    ]
+    .into_iter()
+    .chain(desktop_actions.iter().map(|action| {
+        crate::model::Action::new(
+            &format!("{DESKTOP_ACTION_PREFIX}{}", action.key),
+            &action.name,
+        )
+    }))
+    .collect();
+
+    let entry = crate::model::Entry {
         id: desktop_entry.appid.to_string(),
         title,
-        action: String::from("open"),
+        actions,
         meta,
+        kind: Some(crate::model::EntryKind::Command(cmd.clone())),
+        icon: desktop_entry.icon().and_then(|icon| icons.find(icon)),
         command: Some(cmd),
-    })
+        highlights: vec![],
+        score: 0,
+        matched_with_typos: false,
+    };
+    let details = ApplicationDetails {
+        comment: desktop_entry
+            .comment(Some(&locale))
+            .map(|comment| comment.to_string()),
+        exec: String::from(exec),
+        desktop_actions,
+    };
+    Ok((entry, details))
+}
+
+fn read_desktop_entries() -> anyhow::Result<Vec<(crate::model::Entry, ApplicationDetails)>> {
+    let paths = freedesktop_desktop_entry::Iter::new(freedesktop_desktop_entry::default_paths());
+    let mut icons = crate::plugin::icons::IconLookup::new();
+    let mut entries: Vec<(crate::model::Entry, ApplicationDetails)> = paths
+        .filter_map(|path| {
+            let desktop_entry_result = read_desktop_entry(&path, &mut icons);
+            if let Err(error) = desktop_entry_result {
+                log::warn!(target: "applications", "Skipping desktop entry: '{:?}'.", error);
+                return None;
+            }
+            desktop_entry_result.ok()
+        })
+        .collect();
+
+    entries.sort_by(|(entry, _), (other_entry, _)| entry.cmp(other_entry));
+    entries.dedup_by(|(entry, _), (other_entry, _)| entry == other_entry);
+
+    Ok(entries)
 }
 
 fn is_visible(desktop_entry: &freedesktop_desktop_entry::DesktopEntry) -> bool {
@@ -84,7 +180,7 @@ fn is_visible(desktop_entry: &freedesktop_desktop_entry::DesktopEntry) -> bool {
 
     // filter entries where Exec == false
     if let Some(exec) = desktop_entry.exec() {
-        if exec.to_ascii_lowercase() == "false" {
+        if exec.eq_ignore_ascii_case("false") {
             return false;
         }
     }
@@ -92,58 +188,98 @@ fn is_visible(desktop_entry: &freedesktop_desktop_entry::DesktopEntry) -> bool {
     true
 }
 
-impl Plugin for ApplicationsPlugin {
-    fn new() -> Self {
-        Self { entries: vec![] }
+impl ApplicationsPlugin {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            details: std::collections::HashMap::new(),
+        }
     }
+}
 
-    fn id() -> &'static str {
+#[async_trait::async_trait]
+impl Plugin for ApplicationsPlugin {
+    fn id(&self) -> &str {
         "applications"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         29
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰀻 Apps"
     }
 
+    fn has_preview(&self) -> bool {
+        true
+    }
+
     fn entries(&self) -> Vec<crate::model::Entry> {
         self.entries.clone()
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
+        self.details.clear();
+        for (entry, details) in crate::plugin::utils::spawn_blocking(read_desktop_entries).await? {
+            self.details.insert(entry.id.clone(), details);
+            self.entries.push(entry);
+        }
+        Ok(())
+    }
 
-        let paths =
-            freedesktop_desktop_entry::Iter::new(freedesktop_desktop_entry::default_paths());
-        self.entries = paths
-            .filter_map(|path| {
-                let desktop_entry_result = read_desktop_entry(&path);
-                if let Err(error) = desktop_entry_result {
-                    log::warn!(target: "applications", "Skipping desktop entry: '{:?}'.", error);
-                    return None;
-                }
-                desktop_entry_result.ok()
-            })
-            .collect();
-
-        self.entries.sort();
-        self.entries.dedup();
+    async fn preview(
+        &mut self,
+        entry: &crate::model::Entry,
+    ) -> anyhow::Result<Option<crate::model::Preview>> {
+        let Some(details) = self.details.get(&entry.id) else {
+            return Ok(None);
+        };
 
-        Ok(())
+        let mut fields = vec![];
+        if let Some(comment) = &details.comment {
+            fields.push((String::from("Comment"), comment.clone()));
+        }
+        fields.push((String::from("Exec"), details.exec.clone()));
+        Ok(Some(crate::model::Preview {
+            fields,
+            text: String::new(),
+        }))
     }
 
-    fn activate(
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
-        let command = entry.command.context(format!(
-            "Failed to unpack command while activating entry with id '{}'.",
-            entry.id
-        ))?;
+        let entry_command = || {
+            entry.command.clone().context(format!(
+                "Failed to unpack command while activating entry with id '{}'.",
+                entry.id
+            ))
+        };
+        let command = match action {
+            OPEN => entry_command()?,
+            OPEN_IN_TERMINAL => [crate::plugin::utils::terminal(), String::from("-e")]
+                .into_iter()
+                .chain(entry_command()?)
+                .collect(),
+            action => {
+                let desktop_action = action
+                    .strip_prefix(DESKTOP_ACTION_PREFIX)
+                    .and_then(|key| {
+                        self.details
+                            .get(&entry.id)?
+                            .desktop_actions
+                            .iter()
+                            .find(|desktop_action| desktop_action.key == key)
+                    })
+                    .ok_or_else(|| crate::plugin::utils::unknown_action(&entry, action))?;
+                desktop_action.command.clone()
+            }
+        };
         std::process::Command::new(&command[0])
             .args(&command[1..])
             .spawn()?;
@@ -158,3 +294,111 @@ impl Plugin for ApplicationsPlugin {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::utils::Plugin;
+
+    const DESKTOP_ENTRY: &str = "[Desktop Entry]
+Type=Application
+Name=Firefox
+Exec=firefox %u
+Actions=new-window;new-private-window;broken;
+
+[Desktop Action new-window]
+Name=Open
+Exec=firefox --new-window %u
+
+[Desktop Action new-private-window]
+Name=New Private Window
+Name[de]=Neues privates Fenster
+Exec=firefox --private-window %u
+
+[Desktop Action broken]
+Name=Missing Exec
+";
+
+    fn desktop_actions(locale: &str) -> Vec<super::DesktopAction> {
+        let path = std::path::Path::new("/usr/share/applications/firefox.desktop");
+        let desktop_entry =
+            freedesktop_desktop_entry::DesktopEntry::decode(path, DESKTOP_ENTRY).unwrap();
+        super::read_desktop_actions(&desktop_entry, locale)
+    }
+
+    #[test]
+    fn reads_the_desktop_actions_with_a_command() {
+        assert_eq!(
+            desktop_actions("en_US"),
+            vec![
+                super::DesktopAction {
+                    key: String::from("new-window"),
+                    name: String::from("Open"),
+                    command: vec![String::from("firefox"), String::from("--new-window")],
+                },
+                super::DesktopAction {
+                    key: String::from("new-private-window"),
+                    name: String::from("New Private Window"),
+                    command: vec![String::from("firefox"), String::from("--private-window")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_localized_desktop_action_names() {
+        assert_eq!(desktop_actions("de")[1].name, "Neues privates Fenster");
+    }
+
+    #[test]
+    fn rejects_unknown_actions() {
+        let mut plugin = super::ApplicationsPlugin::new();
+        let entry = crate::model::Entry {
+            id: String::from("firefox"),
+            title: String::from("Firefox"),
+            actions: vec![],
+            meta: String::new(),
+            command: Some(vec![String::from("firefox")]),
+            kind: None,
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        };
+        let (mut plugin_channel_out, _app_channel_in) = iced::futures::channel::mpsc::channel(1);
+
+        for action in ["Open", "desktop-action:new-window", "run"] {
+            let result = iced::futures::executor::block_on(plugin.activate(
+                entry.clone(),
+                action,
+                &mut plugin_channel_out,
+            ));
+            assert!(result.is_err(), "{action}");
+        }
+    }
+
+    #[test]
+    fn keeps_running_after_unknown_actions() {
+        let mut plugin = super::ApplicationsPlugin::new();
+        let entry = crate::model::Entry {
+            id: String::from("firefox"),
+            title: String::from("Firefox"),
+            actions: vec![],
+            meta: String::new(),
+            command: Some(vec![String::from("firefox")]),
+            kind: None,
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        };
+        let (mut plugin_channel_out, _app_channel_in) = iced::futures::channel::mpsc::channel(1);
+
+        let result = iced::futures::executor::block_on(plugin.handle_request(
+            crate::model::PluginRequest::Activate(entry, String::from("run")),
+            &mut plugin_channel_out,
+            &mut crate::search::query::Query::default(),
+            &mut vec![],
+        ));
+        assert!(result.is_ok());
+    }
+}
@@ -5,16 +5,23 @@ pub struct BookmarksPlugin {
     entries: Vec<crate::model::Entry>,
 }
 
+impl BookmarksPlugin {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+#[async_trait::async_trait]
 impl Plugin for BookmarksPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "brave_bookmarks"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         25
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰃃 Bookmarks"
     }
 
@@ -22,33 +29,41 @@ impl Plugin for BookmarksPlugin {
         self.entries.clone()
     }
 
-    fn new() -> Self {
-        Self { entries: vec![] }
-    }
-
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
-        self.entries = crate::plugin::brave::utils::read_bookmarks_file()?
-            .get_bookmarks_recursive(&vec![String::from("Progressive Web Apps")])
-            .into_iter()
-            .map(|bookmark| bookmark.into())
-            .collect();
+        self.entries = crate::plugin::utils::spawn_blocking(|| {
+            let mut entries: Vec<crate::model::Entry> =
+                crate::plugin::brave::utils::read_bookmarks_file()?
+                    .get_bookmarks_recursive(&vec![String::from("Progressive Web Apps")])
+                    .into_iter()
+                    .map(|bookmark| bookmark.into())
+                    .collect();
+            crate::plugin::brave::utils::add_favicons(&mut entries);
+            Ok(entries)
+        })
+        .await?;
 
         Ok(())
     }
 
-    fn activate(
+    fn apply_filters(
+        &self,
+        entries: Vec<crate::model::Entry>,
+        query: &mut crate::search::query::Query,
+    ) -> Vec<crate::model::Entry> {
+        crate::plugin::utils::apply_host_filter(entries, query)
+    }
+
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
-        std::process::Command::new("brave")
-            .arg(&entry.id)
-            .spawn()
-            .context(format!(
-                "Failed to launch brave in app mode while activating entry with id '{}'.",
-                entry.id
-            ))?;
+        crate::plugin::brave::utils::run_url_action(&entry.id, action).context(format!(
+            "Failed to {} while activating entry with id '{}'.",
+            action, entry.id
+        ))?;
 
         plugin_channel_out
             .try_send(crate::Message::Exit)
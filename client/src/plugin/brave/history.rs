@@ -1,83 +1,198 @@
 use crate::plugin::utils::Plugin;
 use anyhow::Context;
+use iced::futures::StreamExt;
+
+type LastVisits = std::collections::HashMap<String, chrono::NaiveDateTime>;
 
 pub struct HistoryPlugin {
     entries: Vec<crate::model::Entry>,
+    last_visits: LastVisits,
+}
+
+// Chromium stores timestamps as microseconds since 1601-01-01.
+const SECONDS_BETWEEN_CHROMIUM_AND_UNIX_EPOCH: i64 = 11_644_473_600;
+
+fn to_local_date_time(chromium_timestamp: i64) -> Option<chrono::NaiveDateTime> {
+    let unix_timestamp = chromium_timestamp / 1_000_000 - SECONDS_BETWEEN_CHROMIUM_AND_UNIX_EPOCH;
+    let date_time = chrono::DateTime::<chrono::Utc>::from_timestamp(unix_timestamp, 0)?;
+    Some(date_time.with_timezone(&chrono::Local).naive_local())
+}
+
+// Rows are handed out in batches, so the history shows up while it is read.
+const BATCH_SIZE: usize = 1_000;
+
+#[derive(Default)]
+struct HistoryBatch {
+    entries: Vec<crate::model::Entry>,
+    last_visits: LastVisits,
+}
+
+/// Reads the visited urls as entries together with the date of their last
+/// visit, the most visited first.
+fn read_history(
+    batches_out: iced::futures::channel::mpsc::UnboundedSender<HistoryBatch>,
+) -> anyhow::Result<()> {
+    let config_directory = crate::plugin::utils::config_directory()?;
+    let history_file_path =
+        format!("{config_directory}/BraveSoftware/Brave-Browser/Default/History");
+
+    let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
+    let history_cache_file_path = format!("{cache_directory}/brave-history.sqlite");
+
+    if !std::path::Path::new(&history_file_path).exists() {
+        anyhow::bail!("Unable to find brave history, is brave installed?");
+    }
+    std::fs::copy(history_file_path, &history_cache_file_path)
+        .context("Error while creating cache directory")?;
+
+    let connection =
+        sqlite::open(history_cache_file_path).context("Error while opening brave history cache")?;
+    let query = "SELECT title, url, last_visit_time FROM urls ORDER BY visit_count DESC, last_visit_time DESC";
+    let url_rows = connection
+        .prepare(query)
+        .context("Error while querying brave history")?
+        .into_iter();
+
+    let mut batch = HistoryBatch::default();
+    for row in url_rows {
+        let row = row.context("Error while reading brave history")?;
+        let title = row.read::<&str, _>("title");
+        let url = row.read::<&str, _>("url");
+        let last_visit_time = row.read::<i64, _>("last_visit_time");
+        if let Some(last_visit) = to_local_date_time(last_visit_time) {
+            batch.last_visits.insert(url.to_string(), last_visit);
+        }
+
+        batch.entries.push(crate::model::Entry {
+            id: url.to_string(),
+            title: title.to_string(),
+            actions: crate::plugin::brave::utils::url_actions(),
+            meta: String::from("History"),
+            command: None,
+            kind: Some(crate::model::EntryKind::Url(url.to_string())),
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        });
+
+        if batch.entries.len() == BATCH_SIZE {
+            // Nobody is waiting for the rest when loading was aborted.
+            if batches_out
+                .unbounded_send(std::mem::take(&mut batch))
+                .is_err()
+            {
+                return Ok(());
+            }
+        }
+    }
+    let _ = batches_out.unbounded_send(batch);
+
+    Ok(())
+}
+
+impl HistoryPlugin {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            last_visits: LastVisits::new(),
+        }
+    }
 }
 
+#[async_trait::async_trait]
 impl Plugin for HistoryPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "brave_history"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         0
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰃃 History"
     }
 
-    fn entries(&self) -> Vec<crate::model::Entry> {
-        self.entries.clone()
+    fn has_preview(&self) -> bool {
+        true
     }
 
-    fn new() -> Self {
-        Self { entries: vec![] }
+    fn entries(&self) -> Vec<crate::model::Entry> {
+        self.entries.clone()
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn stream_entries(
+        &mut self,
+        batches: &mut crate::plugin::utils::EntryBatches<'_>,
+    ) -> anyhow::Result<()> {
         self.entries.clear();
+        self.last_visits.clear();
+
+        let (history_batches_out, mut history_batches_in) =
+            iced::futures::channel::mpsc::unbounded();
+        let reading =
+            crate::plugin::utils::spawn_blocking(move || read_history(history_batches_out));
+        let adding = async {
+            while let Some(history_batch) = history_batches_in.next().await {
+                self.entries.extend(history_batch.entries.iter().cloned());
+                self.last_visits.extend(history_batch.last_visits);
+                batches.loaded(self, history_batch.entries).await?;
+            }
+            Ok(())
+        };
+        iced::futures::future::try_join(reading, adding).await?;
 
-        let config_directory = crate::plugin::utils::config_directory()?;
-        let history_file_path =
-            format!("{config_directory}/BraveSoftware/Brave-Browser/Default/History");
-
-        let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
-        let history_cache_file_path = format!("{cache_directory}/brave-history.sqlite");
-
-        std::fs::copy(history_file_path, &history_cache_file_path)
-            .context("Error while creating cache directory")?;
+        Ok(())
+    }
 
-        let connection = sqlite::open(history_cache_file_path).unwrap();
-        let query = "SELECT title, url FROM urls ORDER BY visit_count DESC, last_visit_time DESC";
-        connection.execute(query).unwrap();
-        let url_rows = connection
-            .prepare(query)
-            .unwrap()
+    fn apply_filters(
+        &self,
+        entries: Vec<crate::model::Entry>,
+        query: &mut crate::search::query::Query,
+    ) -> Vec<crate::model::Entry> {
+        let entries = crate::plugin::utils::apply_host_filter(entries, query);
+
+        let Some(after) = query.filters.after.take() else {
+            return entries;
+        };
+        entries
             .into_iter()
-            .map(|row| row.unwrap());
-
-        self.entries = url_rows
-            .map(|row| {
-                let title = row.read::<&str, _>("title");
-                let url = row.read::<&str, _>("url");
-
-                crate::model::Entry {
-                    id: url.to_string(),
-                    title: title.to_string(),
-                    action: String::from("open"),
-                    meta: String::from("History"),
-                    command: None,
-                }
+            .filter(|entry| {
+                self.last_visits
+                    .get(&entry.id)
+                    .is_some_and(|last_visit| last_visit.date() >= after)
             })
-            .collect();
+            .collect()
+    }
 
-        Ok(())
+    async fn preview(
+        &mut self,
+        entry: &crate::model::Entry,
+    ) -> anyhow::Result<Option<crate::model::Preview>> {
+        let mut fields = vec![(String::from("URL"), entry.id.clone())];
+        if let Some(last_visit) = self.last_visits.get(&entry.id) {
+            fields.push((
+                String::from("Last visit"),
+                last_visit.format("%A, %_d. %B %Y, %H:%M").to_string(),
+            ));
+        }
+        Ok(Some(crate::model::Preview {
+            fields,
+            text: String::new(),
+        }))
     }
 
-    fn activate(
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
-        std::process::Command::new("brave")
-            .arg(&entry.id)
-            .spawn()
-            .context(format!(
-                "Failed to launch brave while activating entry with id '{}'.",
-                entry.id
-            ))?;
+        crate::plugin::brave::utils::run_url_action(&entry.id, action).context(format!(
+            "Failed to {} while activating entry with id '{}'.",
+            action, entry.id
+        ))?;
 
         plugin_channel_out
             .try_send(crate::Message::Exit)
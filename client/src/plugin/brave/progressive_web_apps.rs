@@ -1,20 +1,29 @@
 use crate::plugin::utils::Plugin;
 use anyhow::Context;
 
+const OPEN_IN_NEW_TAB: &str = "open-in-new-tab";
+
 pub struct ProgressiveWebAppsPlugin {
     entries: Vec<crate::model::Entry>,
 }
 
+impl ProgressiveWebAppsPlugin {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+#[async_trait::async_trait]
 impl Plugin for ProgressiveWebAppsPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "brave_progressive_web_apps"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         28
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰀻 Progressive Web Apps"
     }
 
@@ -22,42 +31,63 @@ impl Plugin for ProgressiveWebAppsPlugin {
         self.entries.clone()
     }
 
-    fn new() -> Self {
-        Self { entries: vec![] }
-    }
-
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
 
         let folder_name = String::from("Progressive Web Apps");
-        let bookmarks_root = crate::plugin::brave::utils::read_bookmarks_file()?;
+        let bookmarks_root =
+            crate::plugin::utils::spawn_blocking(crate::plugin::brave::utils::read_bookmarks_file)
+                .await?;
         let pwa_folder = bookmarks_root
             .find_bookmarks_folder_recursive(&folder_name)
             .ok_or(anyhow::anyhow!(
                 "Unable to find a bookmarks folder named '{}'.",
                 folder_name
             ))?;
-        self.entries = pwa_folder
+        let mut entries: Vec<crate::model::Entry> = pwa_folder
             .get_bookmarks_recursive(&vec![])
             .into_iter()
-            .map(|bookmark| bookmark.into())
+            .map(|bookmark| crate::model::Entry {
+                actions: vec![
+                    crate::model::Action::new(crate::plugin::brave::utils::OPEN, "open"),
+                    crate::model::Action::new(OPEN_IN_NEW_TAB, "open in new tab"),
+                ],
+                ..bookmark.into()
+            })
             .collect();
+        self.entries = crate::plugin::utils::spawn_blocking(move || {
+            crate::plugin::brave::utils::add_favicons(&mut entries);
+            Ok(entries)
+        })
+        .await?;
 
         Ok(())
     }
 
-    fn activate(
+    fn apply_filters(
+        &self,
+        entries: Vec<crate::model::Entry>,
+        query: &mut crate::search::query::Query,
+    ) -> Vec<crate::model::Entry> {
+        crate::plugin::utils::apply_host_filter(entries, query)
+    }
+
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
-        std::process::Command::new("brave")
-            .arg(format!("--app={}", entry.id))
-            .spawn()
-            .context(format!(
-                "Failed to launch brave in app mode while activating entry with id '{}'.",
-                entry.id
-            ))?;
+        // Progressive web apps open in app mode unless asked for a tab.
+        let action = match action {
+            crate::plugin::brave::utils::OPEN => crate::plugin::brave::utils::OPEN_IN_APP_MODE,
+            OPEN_IN_NEW_TAB => crate::plugin::brave::utils::OPEN,
+            action => return Err(crate::plugin::utils::unknown_action(&entry, action)),
+        };
+        crate::plugin::brave::utils::run_url_action(&entry.id, action).context(format!(
+            "Failed to {} while activating entry with id '{}'.",
+            action, entry.id
+        ))?;
 
         plugin_channel_out
             .try_send(crate::Message::Exit)
@@ -45,9 +45,14 @@ impl From<&UrlBookmark> for crate::model::Entry {
         crate::model::Entry {
             id: val.url.clone(),
             title: val.name.clone(),
-            action: String::from("open"),
+            actions: url_actions(),
             meta: String::from("Bookmarks"),
             command: None,
+            kind: Some(crate::model::EntryKind::Url(val.url.clone())),
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
         }
     }
 }
@@ -79,11 +84,11 @@ impl Bookmark {
                 if exclude_folders.contains(&folder.name) {
                     return vec![];
                 }
-                return folder
+                folder
                     .children
                     .iter()
                     .flat_map(|b| b.get_bookmarks_recursive(exclude_folders))
-                    .collect();
+                    .collect()
             }
 
             Bookmark::Url(url_bookmark) => vec![url_bookmark],
@@ -99,10 +104,205 @@ pub fn read_bookmarks_file() -> anyhow::Result<Bookmark> {
         .join(".config/BraveSoftware/Brave-Browser/Default/Bookmarks");
 
     let bookmarks_file = std::fs::File::open(index_file_path)
-        .context("Error while opening brave bookmarks file.")?;
+        .context("Unable to find brave bookmarks, is brave installed?")?;
     let reader = std::io::BufReader::new(bookmarks_file);
     let bookmarks_file_content: BookmarksFile =
         serde_json::from_reader(reader).context("Error while reading brave bookmarks file.")?;
 
     Ok(bookmarks_file_content.roots.into())
 }
+
+pub const OPEN: &str = "open";
+pub const OPEN_IN_APP_MODE: &str = "open-in-app-mode";
+
+/// The actions offered for brave urls.
+pub fn url_actions() -> Vec<crate::model::Action> {
+    vec![
+        crate::model::Action::new(OPEN, "open"),
+        crate::model::Action::new(OPEN_IN_APP_MODE, "open in app mode"),
+    ]
+}
+
+/// Runs one of the actions offered for brave urls.
+pub fn run_url_action(url: &str, action: &str) -> anyhow::Result<()> {
+    let argument = match action {
+        OPEN => String::from(url),
+        OPEN_IN_APP_MODE => format!("--app={url}"),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Unknown action '{}' for brave urls.",
+                action
+            ))
+        }
+    };
+    std::process::Command::new("brave")
+        .arg(argument)
+        .spawn()
+        .context("Failed to launch brave.")?;
+    Ok(())
+}
+
+/// Sets the icons of url entries to the favicons brave stored for them. Missing
+/// favicons are not worth failing for, so errors are only logged.
+pub fn add_favicons(entries: &mut [crate::model::Entry]) {
+    let urls: std::collections::HashSet<&str> =
+        entries.iter().map(|entry| entry.id.as_str()).collect();
+    let favicons = match read_favicons(&urls) {
+        Ok(favicons) => favicons,
+        Err(error) => {
+            log::warn!("Failed to read brave favicons: {:?}", error);
+            return;
+        }
+    };
+    for entry in entries.iter_mut() {
+        entry.icon = favicons.get(&entry.id).cloned();
+    }
+}
+
+/// Caches the largest favicon of each of the urls and returns the cached icons by url.
+fn read_favicons(
+    urls: &std::collections::HashSet<&str>,
+) -> anyhow::Result<std::collections::HashMap<String, std::path::PathBuf>> {
+    let config_directory = crate::plugin::utils::config_directory()?;
+    let favicons_file_path = std::path::PathBuf::from(format!(
+        "{config_directory}/BraveSoftware/Brave-Browser/Default/Favicons"
+    ));
+
+    // Brave locks the database while running, so a copy is read instead.
+    let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
+    let favicons_cache_file_path =
+        std::path::PathBuf::from(format!("{cache_directory}/brave-favicons.sqlite"));
+    if !is_copy_up_to_date(&favicons_file_path, &favicons_cache_file_path) {
+        std::fs::copy(&favicons_file_path, &favicons_cache_file_path)
+            .context("Error while copying brave favicons to the cache")?;
+    }
+
+    let connection = sqlite::open(favicons_cache_file_path)
+        .context("Error while opening brave favicons cache")?;
+    let largest_favicons = read_largest_favicons(&connection, urls)?;
+
+    let mut favicons = std::collections::HashMap::new();
+    for (url, image_data) in largest_favicons {
+        match crate::plugin::icons::cache_png("brave-favicon", &image_data) {
+            Ok(icon) => {
+                favicons.insert(url, icon);
+            }
+            Err(error) => log::warn!("Skipping favicon of '{}': {:?}", url, error),
+        }
+    }
+    Ok(favicons)
+}
+
+/// Whether the copy was made after the source was last modified.
+fn is_copy_up_to_date(source: &std::path::Path, copy: &std::path::Path) -> bool {
+    let modified = |path: &std::path::Path| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    match (modified(source), modified(copy)) {
+        (Some(source_modified), Some(copy_modified)) => copy_modified >= source_modified,
+        _ => false,
+    }
+}
+
+/// Reads the largest favicon bitmap of each of the urls from the favicons database.
+fn read_largest_favicons(
+    connection: &sqlite::Connection,
+    urls: &std::collections::HashSet<&str>,
+) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+    // The urls are joined in a temporary table, so only their favicons are read.
+    connection
+        .execute("CREATE TEMP TABLE IF NOT EXISTS entry_urls (url TEXT PRIMARY KEY); DELETE FROM entry_urls; BEGIN;")
+        .context("Error while preparing brave favicons query")?;
+    let mut insert = connection
+        .prepare("INSERT OR IGNORE INTO entry_urls VALUES (?)")
+        .context("Error while preparing brave favicons query")?;
+    for url in urls {
+        insert.reset()?;
+        insert.bind((1, *url))?;
+        insert
+            .next()
+            .context("Error while preparing brave favicons query")?;
+    }
+    connection
+        .execute("COMMIT")
+        .context("Error while preparing brave favicons query")?;
+
+    // With MAX, SQLite takes the other columns from the row with the largest width.
+    let query = "SELECT icon_mapping.page_url, favicon_bitmaps.image_data, MAX(favicon_bitmaps.width) FROM entry_urls JOIN icon_mapping ON icon_mapping.page_url = entry_urls.url JOIN favicon_bitmaps ON favicon_bitmaps.icon_id = icon_mapping.icon_id GROUP BY icon_mapping.page_url";
+    let rows = connection
+        .prepare(query)
+        .context("Error while querying brave favicons")?
+        .into_iter();
+
+    let mut largest_favicons = vec![];
+    for row in rows {
+        let row = row.context("Error while reading brave favicons")?;
+        largest_favicons.push((
+            row.read::<&str, _>("page_url").to_string(),
+            row.read::<&[u8], _>("image_data").to_vec(),
+        ));
+    }
+    Ok(largest_favicons)
+}
+
+#[cfg(test)]
+mod tests {
+    fn favicons_database() -> sqlite::Connection {
+        let connection = sqlite::open(":memory:").unwrap();
+        connection
+            .execute(
+                "CREATE TABLE icon_mapping (page_url TEXT, icon_id INTEGER);
+                CREATE TABLE favicon_bitmaps (icon_id INTEGER, width INTEGER, image_data BLOB);
+                INSERT INTO icon_mapping VALUES ('https://docs.rs', 1), ('https://rhai.rs', 2), ('https://example.com', 3);
+                INSERT INTO favicon_bitmaps VALUES (1, 16, x'10'), (1, 64, x'40'), (1, 32, x'20'), (2, 16, x'11'), (3, 16, x'12');",
+            )
+            .unwrap();
+        connection
+    }
+
+    #[test]
+    fn reads_the_largest_favicon_of_the_urls_only() {
+        let connection = favicons_database();
+        let urls = std::collections::HashSet::from(["https://docs.rs", "https://rhai.rs"]);
+
+        let mut favicons = super::read_largest_favicons(&connection, &urls).unwrap();
+        favicons.sort();
+        assert_eq!(
+            favicons,
+            vec![
+                (String::from("https://docs.rs"), vec![0x40]),
+                (String::from("https://rhai.rs"), vec![0x11]),
+            ]
+        );
+
+        // Reading again replaces the urls of the previous read.
+        let urls = std::collections::HashSet::from(["https://example.com"]);
+        assert_eq!(
+            super::read_largest_favicons(&connection, &urls).unwrap(),
+            vec![(String::from("https://example.com"), vec![0x12])]
+        );
+    }
+
+    #[test]
+    fn copies_are_up_to_date_until_the_source_changes() {
+        let directory = crate::plugin::utils::TestDirectory::new("brave");
+        let source = directory.path().join("Favicons");
+        let copy = directory.path().join("brave-favicons.sqlite");
+        std::fs::write(&source, "favicons").unwrap();
+        assert!(!super::is_copy_up_to_date(&source, &copy));
+
+        std::fs::copy(&source, &copy).unwrap();
+        assert!(super::is_copy_up_to_date(&source, &copy));
+
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(!super::is_copy_up_to_date(&source, &copy));
+    }
+}
@@ -4,45 +4,60 @@ pub struct ClockPlugin {
     entries: Vec<crate::model::Entry>,
 }
 
-impl Plugin for ClockPlugin {
-    fn new() -> Self {
+impl ClockPlugin {
+    pub fn new() -> Self {
         Self { entries: vec![] }
     }
+}
 
-    fn id() -> &'static str {
+#[async_trait::async_trait]
+impl Plugin for ClockPlugin {
+    fn id(&self) -> &str {
         "clock"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         10
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰅐 Clock"
     }
 
-    fn update_timeout() -> Option<std::time::Duration> {
+    fn update_timeout(&self) -> Option<std::time::Duration> {
         Some(std::time::Duration::from_secs(1))
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
 
         let date = chrono::Local::now();
+        let time_title = date.format("%H:%M:%S").to_string();
+        let date_title = date.format("%A, %_d. %B %Y").to_string();
         self.entries = vec![
             crate::model::Entry {
                 id: String::from("time-entry"),
-                title: date.format("%H:%M:%S").to_string(),
-                action: String::from(""),
+                title: time_title.clone(),
+                actions: vec![],
                 meta: String::from("Clock Time"),
                 command: None,
+                kind: Some(crate::model::EntryKind::Text(time_title)),
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             },
             crate::model::Entry {
                 id: String::from("date"),
-                title: date.format("%A, %_d. %B %Y").to_string(),
-                action: String::from(""),
+                title: date_title.clone(),
+                actions: vec![],
                 meta: String::from("Clock Date"),
                 command: None,
+                kind: Some(crate::model::EntryKind::Text(date_title)),
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             },
         ];
 
@@ -0,0 +1,261 @@
+use crate::plugin::utils::Plugin;
+use anyhow::Context;
+
+pub fn spawn(
+    settings: crate::settings::CommandPluginSettings,
+) -> iced::Subscription<crate::Message> {
+    crate::plugin::utils::spawn_with_id(settings.id.clone(), move || {
+        CommandPlugin::new(settings.clone())
+    })
+}
+
+/// A plugin listing the output lines of a command as entries and running
+/// another command with the activated line, like a dmenu script.
+pub struct CommandPlugin {
+    settings: crate::settings::CommandPluginSettings,
+    entries: Vec<crate::model::Entry>,
+}
+
+impl CommandPlugin {
+    pub fn new(settings: crate::settings::CommandPluginSettings) -> Self {
+        Self {
+            settings,
+            entries: vec![],
+        }
+    }
+
+    fn parse_line(&self, line: &str) -> crate::model::Entry {
+        let values: Vec<&str> = match &self.settings.separator {
+            Some(separator) if !separator.is_empty() => line.split(separator.as_str()).collect(),
+            _ => vec![line],
+        };
+        let field = |field: crate::settings::CommandField| {
+            self.settings
+                .fields
+                .iter()
+                .zip(values.iter())
+                .find(|(name, _)| **name == field)
+                .map(|(_, value)| value.trim())
+        };
+
+        let title = field(crate::settings::CommandField::Title).unwrap_or(line);
+        let id = field(crate::settings::CommandField::Id).unwrap_or(line);
+        let meta = field(crate::settings::CommandField::Meta).unwrap_or_default();
+
+        let placeholders = [
+            ("{id}", id),
+            ("{title}", title),
+            ("{meta}", meta),
+            ("{line}", line),
+        ];
+        let command = self
+            .settings
+            .activate
+            .iter()
+            .map(|argument| substitute(argument, &placeholders))
+            .collect();
+
+        crate::model::Entry {
+            id: String::from(id),
+            title: String::from(title),
+            actions: vec![crate::model::Action::new("run", "run")],
+            meta: String::from(meta),
+            command: Some(command),
+            kind: None,
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        }
+    }
+}
+
+/// Replaces the placeholders in the template by their values in a single
+/// pass, so placeholders within the inserted values are kept as they are.
+fn substitute(template: &str, placeholders: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            Some((placeholder, value)) => {
+                result.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[async_trait::async_trait]
+impl Plugin for CommandPlugin {
+    fn id(&self) -> &str {
+        &self.settings.id
+    }
+
+    fn priority(&self) -> u32 {
+        self.settings.priority
+    }
+
+    fn title(&self) -> &str {
+        &self.settings.title
+    }
+
+    fn update_timeout(&self) -> Option<std::time::Duration> {
+        self.settings
+            .update_interval
+            .map(std::time::Duration::from_secs)
+    }
+
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
+        let list_command = self.settings.list.clone();
+        let output = crate::plugin::utils::spawn_blocking(move || {
+            let program = list_command
+                .first()
+                .context("Failed to list entries, the list command is empty.")?;
+            let output = std::process::Command::new(program)
+                .args(&list_command[1..])
+                .output()
+                .context(format!("Failed to run list command '{program}'."))?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "List command '{}' failed with {}: {}",
+                    list_command.join(" "),
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(output)
+        })
+        .await?;
+
+        self.entries = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| self.parse_line(line))
+            .collect();
+
+        Ok(())
+    }
+
+    fn entries(&self) -> Vec<crate::model::Entry> {
+        self.entries.clone()
+    }
+
+    async fn activate(
+        &mut self,
+        entry: crate::model::Entry,
+        action: &str,
+        plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
+    ) -> anyhow::Result<()> {
+        if action != "run" {
+            return Err(crate::plugin::utils::unknown_action(&entry, action));
+        }
+        let command = entry
+            .command
+            .filter(|command| !command.is_empty())
+            .context(format!(
+                "Failed to unpack command while activating entry with id '{}'.",
+                entry.id
+            ))?;
+        std::process::Command::new(&command[0])
+            .args(&command[1..])
+            .spawn()?;
+
+        plugin_channel_out
+            .try_send(crate::Message::Exit)
+            .context(format!(
+                "Failed to send message to exit application while activating entry with id '{}'.",
+                entry.id
+            ))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn command_plugin(separator: Option<&str>, fields: &str) -> super::CommandPlugin {
+        let mut settings: crate::settings::CommandPluginSettings = serde_yaml::from_str(&format!(
+            "id: test\ntitle: Test\nlist: [ls]\nactivate: [open, \"{{id}}\", \"{{title}}: {{meta}}\", \"{{line}}\"]\nfields: {fields}\n"
+        ))
+        .unwrap();
+        settings.separator = separator.map(String::from);
+        super::CommandPlugin::new(settings)
+    }
+
+    fn command(entry: &crate::model::Entry) -> Vec<&str> {
+        entry.command.iter().flatten().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn uses_the_whole_line_without_separator() {
+        let plugin = command_plugin(None, "[title, meta, id]");
+        let entry = plugin.parse_line("notes.md\tmarkdown");
+
+        assert_eq!(entry.title, "notes.md\tmarkdown");
+        assert_eq!(entry.id, "notes.md\tmarkdown");
+        assert_eq!(entry.meta, "");
+    }
+
+    #[test]
+    fn splits_lines_into_the_fields() {
+        let plugin = command_plugin(Some("\t"), "[id, title, meta]");
+        let entry = plugin.parse_line("~/notes.md\t Notes \tmarkdown");
+
+        assert_eq!(entry.id, "~/notes.md");
+        assert_eq!(entry.title, "Notes");
+        assert_eq!(entry.meta, "markdown");
+        assert_eq!(
+            command(&entry),
+            vec![
+                "open",
+                "~/notes.md",
+                "Notes: markdown",
+                "~/notes.md\t Notes \tmarkdown"
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_line_for_missing_fields() {
+        let plugin = command_plugin(Some(","), "[title, meta, id]");
+        let entry = plugin.parse_line("Notes");
+
+        assert_eq!(entry.title, "Notes");
+        assert_eq!(entry.id, "Notes");
+        assert_eq!(entry.meta, "");
+
+        let plugin = command_plugin(Some(""), "[title, meta, id]");
+        assert_eq!(plugin.parse_line("a,b").title, "a,b");
+    }
+
+    #[test]
+    fn keeps_placeholders_within_values() {
+        let plugin = command_plugin(Some(","), "[title, meta, id]");
+        let entry = plugin.parse_line("{meta} {line},{id},x");
+
+        assert_eq!(
+            command(&entry),
+            vec!["open", "x", "{meta} {line}: {id}", "{meta} {line},{id},x"]
+        );
+    }
+
+    #[test]
+    fn substitutes_placeholders_once() {
+        let placeholders = [("{id}", "{title}"), ("{title}", "Notes")];
+        assert_eq!(
+            super::substitute("{id} {title} {unknown} {", &placeholders),
+            "{title} Notes {unknown} {"
+        );
+    }
+}
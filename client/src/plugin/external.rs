@@ -0,0 +1,499 @@
+use anyhow::Context;
+use iced::futures::StreamExt;
+
+/// A line sent to the plugin process on stdin.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Request {
+    /// Sent once after start. The plugin answers with its initial entries.
+    Register {
+        id: String,
+        title: String,
+        priority: u32,
+    },
+    /// The query changed. Entries are filtered by centerpiece, so answering
+    /// is only needed for plugins producing entries from the query. Entries
+    /// answering the search send its `generation` back.
+    Search { query: String, generation: u64 },
+    /// Sent every `update_interval` seconds, if configured.
+    Timeout,
+    /// The user ran an action, `entry.action` or one of
+    /// `entry.secondary_actions`, on one of the entries of the plugin.
+    Activate {
+        entry: ExternalEntry,
+        action: String,
+    },
+}
+
+/// A line read from the plugin process on stdout.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Response {
+    /// Replaces all entries of the plugin. `generation` is the one of the
+    /// search answered, entries without it are taken as answering the latest.
+    Entries {
+        entries: Vec<ExternalEntry>,
+        #[serde(default)]
+        generation: Option<u64>,
+    },
+    /// Closes centerpiece, usually after activating an entry.
+    Exit,
+}
+
+fn default_action() -> String {
+    String::from("open")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExternalEntry {
+    pub id: String,
+    pub title: String,
+    /// The action run on enter.
+    #[serde(default = "default_action")]
+    pub action: String,
+    /// Further actions listed in the action menu.
+    #[serde(default)]
+    pub secondary_actions: Vec<String>,
+    #[serde(default)]
+    pub meta: String,
+    #[serde(default)]
+    pub command: Option<Vec<String>>,
+    /// What the entry stands for, like `{"url": "https://..."}`.
+    #[serde(default)]
+    pub kind: Option<crate::model::EntryKind>,
+}
+
+impl From<ExternalEntry> for crate::model::Entry {
+    fn from(entry: ExternalEntry) -> Self {
+        crate::model::Entry {
+            id: entry.id,
+            title: entry.title,
+            actions: std::iter::once(entry.action)
+                .chain(entry.secondary_actions)
+                .map(|action| crate::model::Action::new(&action, &action))
+                .collect(),
+            meta: entry.meta,
+            command: entry.command,
+            kind: entry.kind,
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        }
+    }
+}
+
+impl From<crate::model::Entry> for ExternalEntry {
+    fn from(entry: crate::model::Entry) -> Self {
+        let mut actions = entry.actions.into_iter().map(|action| action.id);
+        ExternalEntry {
+            id: entry.id,
+            title: entry.title,
+            action: actions.next().unwrap_or_else(default_action),
+            secondary_actions: actions.collect(),
+            meta: entry.meta,
+            command: entry.command,
+            kind: entry.kind,
+        }
+    }
+}
+
+/// A running plugin process speaking line delimited JSON.
+pub struct PluginProcess {
+    child: std::process::Child,
+    // Lines written to stdin by a dedicated thread, so a plugin that stops
+    // reading never blocks the executor.
+    requests_out: std::sync::mpsc::Sender<String>,
+}
+
+impl PluginProcess {
+    /// Starts the command and returns the process together with the stream of
+    /// its responses. The stream ends when the process closes its stdout.
+    pub fn spawn(
+        command: &[String],
+    ) -> anyhow::Result<(
+        Self,
+        iced::futures::channel::mpsc::UnboundedReceiver<Response>,
+    )> {
+        let program = command
+            .first()
+            .context("Failed to start plugin process, the command is empty.")?;
+        let mut child = std::process::Command::new(program)
+            .args(&command[1..])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .context(format!("Failed to start plugin process '{program}'."))?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("Failed to open stdin of plugin process.")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("Failed to open stdout of plugin process.")?;
+
+        let (responses_out, responses_in) = iced::futures::channel::mpsc::unbounded();
+        std::thread::spawn(move || {
+            let lines = std::io::BufRead::lines(std::io::BufReader::new(stdout));
+            for line in lines.map_while(Result::ok) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Response>(&line) {
+                    Ok(response) => {
+                        if responses_out.unbounded_send(response).is_err() {
+                            break;
+                        }
+                    }
+                    Err(error) => log::warn!("Ignoring invalid plugin response '{line}': {error}"),
+                }
+            }
+        });
+
+        let (requests_out, requests_in) = std::sync::mpsc::channel::<String>();
+        std::thread::spawn(move || {
+            for line in requests_in {
+                let write_result = std::io::Write::write_all(&mut stdin, line.as_bytes())
+                    .and_then(|_| std::io::Write::flush(&mut stdin));
+                if let Err(error) = write_result {
+                    log::warn!("Failed to write request to plugin process: {error}");
+                    break;
+                }
+            }
+        });
+
+        Ok((
+            Self {
+                child,
+                requests_out,
+            },
+            responses_in,
+        ))
+    }
+
+    /// Queues the request for the plugin process without waiting for it to
+    /// be read.
+    pub fn send(&mut self, request: &Request) -> anyhow::Result<()> {
+        let mut line =
+            serde_json::to_string(request).context("Failed to serialize plugin request.")?;
+        line.push('\n');
+
+        self.requests_out
+            .send(line)
+            .context("Failed to write request to plugin process, it stopped reading requests.")
+    }
+}
+
+impl Drop for PluginProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Events only live for one iteration of the loop, boxing requests gains nothing.
+#[allow(clippy::large_enum_variant)]
+enum Event {
+    Request(crate::model::PluginRequest),
+    Response(Response),
+    Exited,
+}
+
+pub fn spawn(
+    settings: crate::settings::ExternalPluginSettings,
+) -> iced::Subscription<crate::Message> {
+    iced::subscription::channel(
+        settings.id.clone(),
+        100,
+        |mut plugin_channel_out| async move {
+            let mut supervisor = crate::plugin::supervisor::Supervisor::new(&settings.id);
+
+            let (app_channel_out, mut plugin_channel_in) =
+                iced::futures::channel::mpsc::channel(100);
+            let register_result = plugin_channel_out
+                .try_send(crate::Message::RegisterPlugin(crate::model::Plugin {
+                    id: settings.id.clone(),
+                    priority: settings.priority,
+                    title: settings.title.clone(),
+                    app_channel_out,
+                    entries: vec![],
+                    status: crate::model::PluginStatus::default(),
+                    search_pending: false,
+                    has_preview: false,
+                }))
+                .context("Failed to send message to register plugin.");
+            if let Err(error) = register_result {
+                log::error!(target: settings.id.as_str(), "{:?}", error);
+            }
+            let mut last_query = crate::search::query::Query::default();
+
+            loop {
+                let error = supervisor
+                    .run(main(
+                        &settings,
+                        &mut plugin_channel_out,
+                        &mut plugin_channel_in,
+                        &mut last_query,
+                    ))
+                    .await;
+                supervisor.recover(error, &mut plugin_channel_out).await;
+            }
+        },
+    )
+}
+
+/// Starts the plugin process and relays requests and responses until it fails.
+async fn main(
+    settings: &crate::settings::ExternalPluginSettings,
+    plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
+    plugin_channel_in: &mut iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+    last_query: &mut crate::search::query::Query,
+) -> anyhow::Result<()> {
+    let (mut process, responses) = PluginProcess::spawn(&settings.command)?;
+    process.send(&Request::Register {
+        id: settings.id.clone(),
+        title: settings.title.clone(),
+        priority: settings.priority,
+    })?;
+    // A restarted process continues with the current query.
+    if !last_query.text.is_empty() {
+        process.send(&Request::Search {
+            query: last_query.text.clone(),
+            generation: last_query.generation,
+        })?;
+    }
+    plugin_channel_out
+        .try_send(crate::Message::UpdatePluginStatus(
+            settings.id.clone(),
+            crate::model::PluginStatus::Ready,
+        ))
+        .context("Failed to send message to update plugin status.")?;
+
+    let mut events = iced::futures::stream::select(
+        plugin_channel_in.by_ref().map(Event::Request),
+        responses
+            .map(Event::Response)
+            .chain(iced::futures::stream::iter([Event::Exited])),
+    );
+    let update_timeout = settings.update_interval.map(std::time::Duration::from_secs);
+    let mut entries: Vec<crate::model::Entry> = vec![];
+
+    loop {
+        let event = match update_timeout {
+            Some(update_timeout) => async_std::future::timeout(update_timeout, events.next())
+                .await
+                .unwrap_or(Some(Event::Request(crate::model::PluginRequest::Timeout))),
+            None => events.next().await,
+        };
+
+        // The generation of the query the entries belong to. Replies to
+        // outdated searches keep theirs, so the app drops them.
+        let entries_generation = match event {
+            Some(Event::Request(crate::model::PluginRequest::Search(query))) => {
+                process.send(&Request::Search {
+                    query: query.text.clone(),
+                    generation: query.generation,
+                })?;
+                *last_query = query;
+                last_query.generation
+            }
+            Some(Event::Request(crate::model::PluginRequest::Timeout)) => {
+                process.send(&Request::Timeout)?;
+                continue;
+            }
+            // The protocol has no previews yet.
+            Some(Event::Request(crate::model::PluginRequest::Preview(_))) => continue,
+            Some(Event::Request(crate::model::PluginRequest::Activate(entry, action))) => {
+                process.send(&Request::Activate {
+                    entry: entry.into(),
+                    action,
+                })?;
+                continue;
+            }
+            Some(Event::Response(Response::Entries {
+                entries: external_entries,
+                generation,
+            })) => {
+                entries = external_entries.into_iter().map(Into::into).collect();
+                generation.unwrap_or(last_query.generation)
+            }
+            Some(Event::Response(Response::Exit)) => {
+                plugin_channel_out
+                    .try_send(crate::Message::Exit)
+                    .context("Failed to send message to exit application.")?;
+                continue;
+            }
+            Some(Event::Exited) | None => {
+                anyhow::bail!("Plugin process '{}' exited.", settings.command.join(" "))
+            }
+        };
+
+        let filtered_entries = filter_entries(&settings.id, entries.clone(), last_query).await?;
+        plugin_channel_out
+            .try_send(crate::Message::UpdateEntries(
+                settings.id.clone(),
+                entries_generation,
+                filtered_entries,
+            ))
+            .context(format!(
+                "Failed to send message to update entries while searching for '{}'.",
+                last_query.text
+            ))?;
+    }
+}
+
+/// The entries of the plugin matching the query, filtered like the results
+/// of built-in plugins.
+async fn filter_entries(
+    plugin_id: &str,
+    entries: Vec<crate::model::Entry>,
+    query: &crate::search::query::Query,
+) -> anyhow::Result<Vec<crate::model::Entry>> {
+    let mut filtered_entries =
+        crate::plugin::utils::spawn_search(plugin_id, entries, query.clone()).await?;
+    crate::plugin::utils::retain_kind(&mut filtered_entries, query);
+    Ok(filtered_entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PluginProcess, Request, Response};
+    use iced::futures::StreamExt;
+
+    // Built by cargo next to the test binary from `examples/external_plugin.rs`.
+    fn example_plugin_command() -> Vec<String> {
+        let test_binary = std::env::current_exe().unwrap();
+        let target_directory = test_binary.parent().unwrap().parent().unwrap();
+        let example_plugin = target_directory.join("examples").join("external_plugin");
+        vec![example_plugin.to_string_lossy().into_owned()]
+    }
+
+    fn entry_titles(response: Option<Response>) -> Vec<String> {
+        match response {
+            Some(Response::Entries { entries, .. }) => {
+                entries.into_iter().map(|entry| entry.title).collect()
+            }
+            other => panic!("Expected entries, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn speaks_the_whole_protocol() {
+        iced::futures::executor::block_on(async {
+            let (mut process, mut responses) =
+                PluginProcess::spawn(&example_plugin_command()).unwrap();
+
+            process
+                .send(&Request::Register {
+                    id: String::from("example"),
+                    title: String::from("Example"),
+                    priority: 0,
+                })
+                .unwrap();
+            let titles = entry_titles(responses.next().await);
+            assert_eq!(titles, vec!["Hello from Example", "Exit centerpiece"]);
+
+            process
+                .send(&Request::Search {
+                    query: String::from("rust"),
+                    generation: 7,
+                })
+                .unwrap();
+            let response = responses.next().await;
+            assert!(matches!(
+                response,
+                Some(Response::Entries {
+                    generation: Some(7),
+                    ..
+                })
+            ));
+            let titles = entry_titles(response);
+            assert!(titles.contains(&String::from("Search the web for 'rust'")));
+
+            process.send(&Request::Timeout).unwrap();
+            let titles = entry_titles(responses.next().await);
+            assert!(titles.contains(&String::from("Updated 1 time(s)")));
+
+            process
+                .send(&Request::Activate {
+                    entry: super::ExternalEntry {
+                        id: String::from("exit"),
+                        title: String::from("Exit centerpiece"),
+                        action: String::from("exit"),
+                        secondary_actions: vec![],
+                        meta: String::new(),
+                        command: None,
+                        kind: None,
+                    },
+                    action: String::from("exit"),
+                })
+                .unwrap();
+            assert!(matches!(responses.next().await, Some(Response::Exit)));
+
+            drop(process);
+            assert!(responses.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn sends_without_waiting_for_the_process_to_read() {
+        // `sleep` never reads its stdin, so blocking writes would stall once
+        // the pipe buffer is full.
+        let command = vec![String::from("sleep"), String::from("10")];
+        let (mut process, _responses) = PluginProcess::spawn(&command).unwrap();
+        let query = "x".repeat(1024);
+        for _ in 0..1024 {
+            process
+                .send(&Request::Search {
+                    query: query.clone(),
+                    generation: 0,
+                })
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn reads_entry_kinds() {
+        let entry: super::ExternalEntry = serde_json::from_str(
+            r#"{"id": "docs", "title": "Docs", "kind": {"url": "https://docs.rs"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            entry.kind,
+            Some(crate::model::EntryKind::Url(String::from(
+                "https://docs.rs"
+            )))
+        );
+        assert_eq!(entry.action, "open");
+    }
+
+    #[test]
+    fn applies_the_kind_filter_to_the_entries_of_external_plugins() {
+        let entries: Vec<crate::model::Entry> = serde_json::from_str::<Vec<super::ExternalEntry>>(
+            r#"[
+                {"id": "docs", "title": "Docs", "kind": {"url": "https://docs.rs"}},
+                {"id": "notes", "title": "Docs notes", "kind": {"path": "/home/me/notes"}},
+                {"id": "plain", "title": "Docs plain"}
+            ]"#,
+        )
+        .unwrap()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+        let filtered_entries = iced::futures::executor::block_on(super::filter_entries(
+            "external",
+            entries,
+            &crate::search::query::Query::parse("kind:url docs"),
+        ))
+        .unwrap();
+
+        let ids: Vec<&str> = filtered_entries
+            .iter()
+            .map(|entry| entry.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["docs"]);
+    }
+}
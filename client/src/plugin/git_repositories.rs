@@ -3,40 +3,102 @@ use anyhow::Context;
 
 pub struct GitRepositoriesPlugin {
     entries: Vec<crate::model::Entry>,
-    settings: crate::settings::Settings,
+    settings: crate::settings::GitRepositoriesPluginSettings,
 }
 
+impl GitRepositoriesPlugin {
+    pub fn new(settings: crate::settings::GitRepositoriesPluginSettings) -> Self {
+        Self {
+            entries: vec![],
+            settings,
+        }
+    }
+}
+
+/// Runs git in the repository and returns its trimmed output.
+fn git(repository: &str, arguments: &[&str]) -> anyhow::Result<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(arguments)
+        .output()
+        .context("Failed to run git.")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to run 'git {}' in '{}': {}",
+            arguments.join(" "),
+            repository,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+fn read_git_preview(repository: String) -> anyhow::Result<crate::model::Preview> {
+    let branch = git(&repository, &["branch", "--show-current"])?;
+    let status = git(&repository, &["status", "--short"])?;
+    Ok(crate::model::Preview {
+        fields: vec![
+            (String::from("Path"), repository),
+            (
+                String::from("Branch"),
+                match branch.is_empty() {
+                    true => String::from("detached HEAD"),
+                    false => branch,
+                },
+            ),
+        ],
+        text: match status.is_empty() {
+            true => String::from("nothing to commit, working tree clean"),
+            false => status,
+        },
+    })
+}
+
+#[async_trait::async_trait]
 impl Plugin for GitRepositoriesPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "git_repositories"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         28
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰘬 Git Repositories"
     }
 
-    fn entries(&self) -> Vec<crate::model::Entry> {
-        self.entries.clone()
+    fn has_preview(&self) -> bool {
+        true
     }
 
-    fn new() -> Self {
-        Self {
-            entries: vec![],
-            settings: crate::settings::Settings::new(),
-        }
+    fn entries(&self) -> Vec<crate::model::Entry> {
+        self.entries.clone()
     }
 
     // This lint seems to be a false positive
     #[allow(clippy::unnecessary_filter_map)]
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
 
-        let git_repository_paths: Vec<String> =
-            crate::plugin::utils::read_index_file("git-repositories-index.json")?;
+        let git_repository_paths: Vec<String> = crate::plugin::utils::spawn_blocking(|| {
+            crate::plugin::utils::read_index_file("git-repositories-index.json")
+        })
+        .await
+        .map_err(|error| {
+            let is_missing = error.chain().any(|cause| {
+                cause
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|io_error| io_error.kind() == std::io::ErrorKind::NotFound)
+            });
+            match is_missing {
+                true => error.context("index not found, run index-git-repositories"),
+                false => error.context("Failed to read the git repositories index."),
+            }
+        })?;
 
         let home = std::env::var("HOME").unwrap_or(String::from(""));
 
@@ -44,13 +106,20 @@ impl Plugin for GitRepositoriesPlugin {
             .into_iter()
             .filter_map(|git_repository_path| {
                 let git_repository_display_name = git_repository_path.replacen(&home, "~", 1);
+                let kind =
+                    crate::model::EntryKind::Path(std::path::PathBuf::from(&git_repository_path));
 
                 Some(crate::model::Entry {
                     id: git_repository_path,
                     title: git_repository_display_name,
-                    action: String::from("focus"),
+                    actions: vec![crate::model::Action::new("focus", "focus")],
                     meta: String::from("Git Repositories"),
                     command: None,
+                    kind: Some(kind),
+                    icon: None,
+                    highlights: vec![],
+                    score: 0,
+                    matched_with_typos: false,
                 })
             })
             .collect();
@@ -58,12 +127,26 @@ impl Plugin for GitRepositoriesPlugin {
         Ok(())
     }
 
-    fn activate(
+    async fn preview(
+        &mut self,
+        entry: &crate::model::Entry,
+    ) -> anyhow::Result<Option<crate::model::Preview>> {
+        let repository = entry.id.clone();
+        let preview =
+            crate::plugin::utils::spawn_blocking(move || read_git_preview(repository)).await?;
+        Ok(Some(preview))
+    }
+
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
-        for command in self.settings.plugin.git_repositories.commands.clone() {
+        if action != "focus" {
+            return Err(crate::plugin::utils::unknown_action(&entry, action));
+        }
+        for command in self.settings.commands.clone() {
             let parsed_command: Vec<String> = command
                 .into_iter()
                 .map(|command_part| match command_part.as_ref() {
@@ -0,0 +1,328 @@
+use anyhow::Context;
+
+// Entries show icons at about this size, so the closest bitmaps are preferred.
+const PREFERRED_SIZE: u32 = 32;
+
+/// Resolves icon names through the freedesktop icon theme lookup to SVG or
+/// PNG files.
+#[derive(Default)]
+pub struct IconLookup {
+    // The icon files by name, indexed on first use since walking the themes is slow.
+    index: Option<std::collections::HashMap<String, std::path::PathBuf>>,
+    resolved: std::collections::HashMap<String, Option<std::path::PathBuf>>,
+}
+
+impl IconLookup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finds the icon with the name or absolute path, as used by the `Icon`
+    /// key of desktop entries.
+    pub fn find(&mut self, name: &str) -> Option<std::path::PathBuf> {
+        if let Some(resolved) = self.resolved.get(name) {
+            return resolved.clone();
+        }
+
+        let resolved = self.resolve(name).unwrap_or_else(|error| {
+            log::warn!("Failed to resolve icon '{}': {:?}", name, error);
+            None
+        });
+        self.resolved.insert(String::from(name), resolved.clone());
+        resolved
+    }
+
+    fn resolve(&mut self, name: &str) -> anyhow::Result<Option<std::path::PathBuf>> {
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        let source = if std::path::Path::new(name).is_absolute() {
+            std::path::PathBuf::from(name)
+        } else {
+            let name = name
+                .strip_suffix(".svg")
+                .or_else(|| name.strip_suffix(".png"))
+                .unwrap_or(name);
+            match self.index.get_or_insert_with(index_icons).get(name) {
+                Some(path) => path.clone(),
+                None => return Ok(None),
+            }
+        };
+
+        let extension = source.extension().and_then(std::ffi::OsStr::to_str);
+        if extension != Some("svg") && extension != Some("png") {
+            return Ok(None);
+        }
+        match std::fs::metadata(&source) {
+            Ok(_) => Ok(Some(source)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => {
+                Err(error).context(format!("Failed to read icon '{}'.", source.display()))
+            }
+        }
+    }
+}
+
+/// Caches a PNG image under the name and a hash of its content, unless it is
+/// cached already, and returns the path of the cached icon.
+pub fn cache_png(name: &str, png: &[u8]) -> anyhow::Result<std::path::PathBuf> {
+    if !png.starts_with(PNG_SIGNATURE) {
+        anyhow::bail!("The icon is not a PNG image.");
+    }
+    let cached_icon_path = cached_icon_path(name, &cache_key(png))?;
+    if !cached_icon_path.exists() {
+        write_cached_icon(&cached_icon_path, png)?;
+    }
+    Ok(cached_icon_path)
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Identifies the content of an icon, so changed icons are cached anew.
+fn cache_key(png: &[u8]) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::hash::Hash::hash(png, &mut hasher);
+    format!("{:016x}", std::hash::Hasher::finish(&hasher))
+}
+
+fn cached_icon_path(name: &str, cache_key: &str) -> anyhow::Result<std::path::PathBuf> {
+    let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
+    Ok(std::path::Path::new(&cache_directory)
+        .join("icons")
+        .join(cached_icon_file_name(name, cache_key)))
+}
+
+fn cached_icon_file_name(name: &str, cache_key: &str) -> String {
+    format!("{}-{cache_key}.png", name.replace('/', "_"))
+}
+
+fn write_cached_icon(path: &std::path::Path, png: &[u8]) -> anyhow::Result<()> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).context("Failed to create the icon cache directory.")?;
+    }
+    std::fs::write(path, png).context(format!("Failed to cache icon '{}'.", path.display()))
+}
+
+/// Indexes the icons of the icon theme, the themes it inherits from and
+/// hicolor, earlier themes taking precedence, followed by the pixmaps.
+fn index_icons() -> std::collections::HashMap<String, std::path::PathBuf> {
+    let data_directories = data_directories();
+    let mut base_directories: Vec<std::path::PathBuf> = vec![];
+    if let Ok(home_directory) = std::env::var("HOME") {
+        base_directories.push(std::path::Path::new(&home_directory).join(".icons"));
+    }
+    base_directories.extend(
+        data_directories
+            .iter()
+            .map(|directory| directory.join("icons")),
+    );
+
+    let mut pending_themes: std::collections::VecDeque<String> =
+        icon_theme_name().into_iter().collect();
+    let mut themes: Vec<String> = vec![];
+    while let Some(theme) = pending_themes.pop_front() {
+        if theme == "hicolor" || themes.contains(&theme) {
+            continue;
+        }
+        pending_themes.extend(parent_themes(&base_directories, &theme));
+        themes.push(theme);
+    }
+    themes.push(String::from("hicolor"));
+
+    let mut index = std::collections::HashMap::new();
+    for theme in themes {
+        let mut theme_index = std::collections::HashMap::new();
+        for base_directory in base_directories.iter() {
+            index_theme_directory(&base_directory.join(&theme), &mut theme_index);
+        }
+        for (name, (_, path)) in theme_index {
+            index.entry(name).or_insert(path);
+        }
+    }
+
+    for directory in data_directories {
+        for (name, path) in icon_files(&directory.join("pixmaps")) {
+            index.entry(name).or_insert(path);
+        }
+    }
+
+    index
+}
+
+/// Adds the icons of a theme directory with the distance of their size to the
+/// preferred size, keeping the closest icon for every name. Themes nest them
+/// as `<size>/<context>` like hicolor or as `<context>/<size>` like breeze.
+fn index_theme_directory(
+    theme_directory: &std::path::Path,
+    theme_index: &mut std::collections::HashMap<String, (u32, std::path::PathBuf)>,
+) {
+    for outer_directory in subdirectories(theme_directory) {
+        for inner_directory in subdirectories(&outer_directory) {
+            let distance = icon_size(&outer_directory)
+                .or_else(|| icon_size(&inner_directory))
+                .map_or(u32::MAX, |size| size.abs_diff(PREFERRED_SIZE));
+            for (name, path) in icon_files(&inner_directory) {
+                match theme_index.get(&name) {
+                    Some((indexed_distance, _)) if *indexed_distance <= distance => {}
+                    _ => {
+                        theme_index.insert(name, (distance, path));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The size of the icons in a theme subdirectory like `48x48`, `48x48@2` or
+/// `48`. Scalable icons fit any size.
+fn icon_size(directory: &std::path::Path) -> Option<u32> {
+    let name = directory.file_name()?.to_str()?;
+    if name == "scalable" {
+        return Some(PREFERRED_SIZE);
+    }
+    let digits: String = name.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+fn subdirectories(directory: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+/// The SVG and PNG icons in the directory by name.
+fn icon_files(directory: &std::path::Path) -> Vec<(String, std::path::PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let extension = path.extension()?.to_str()?;
+            if extension != "svg" && extension != "png" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some((name, path))
+        })
+        .collect()
+}
+
+fn data_directories() -> Vec<std::path::PathBuf> {
+    let mut data_directories = vec![];
+    match std::env::var("XDG_DATA_HOME") {
+        Ok(data_home) => data_directories.push(std::path::PathBuf::from(data_home)),
+        Err(_) => {
+            if let Ok(home_directory) = std::env::var("HOME") {
+                data_directories.push(std::path::Path::new(&home_directory).join(".local/share"));
+            }
+        }
+    }
+    let data_dirs =
+        std::env::var("XDG_DATA_DIRS").unwrap_or(String::from("/usr/local/share:/usr/share"));
+    data_directories.extend(
+        data_dirs
+            .split(':')
+            .filter(|directory| !directory.is_empty())
+            .map(std::path::PathBuf::from),
+    );
+    data_directories
+}
+
+/// The icon theme configured for gtk, which most desktops set.
+fn icon_theme_name() -> Option<String> {
+    let config_directory = crate::plugin::utils::config_directory().ok()?;
+    let settings =
+        std::fs::read_to_string(format!("{config_directory}/gtk-3.0/settings.ini")).ok()?;
+    settings.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "gtk-icon-theme-name").then(|| value.trim().to_string())
+    })
+}
+
+/// The themes listed by the `Inherits` key of the theme's `index.theme`.
+fn parent_themes(base_directories: &[std::path::PathBuf], theme: &str) -> Vec<String> {
+    let Some(index_theme) = base_directories.iter().find_map(|directory| {
+        std::fs::read_to_string(directory.join(theme).join("index.theme")).ok()
+    }) else {
+        return vec![];
+    };
+    index_theme
+        .lines()
+        .find_map(|line| line.strip_prefix("Inherits="))
+        .map(|inherits| {
+            inherits
+                .split(',')
+                .map(str::trim)
+                .filter(|parent| !parent.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn icon_size_is_read_from_theme_directories() {
+        let icon_size = |name: &str| super::icon_size(std::path::Path::new(name));
+        assert_eq!(icon_size("/usr/share/icons/hicolor/48x48"), Some(48));
+        assert_eq!(icon_size("/usr/share/icons/Papirus/24x24@2x"), Some(24));
+        assert_eq!(icon_size("/usr/share/icons/breeze/apps/16"), Some(16));
+        assert_eq!(
+            icon_size("/usr/share/icons/hicolor/scalable"),
+            Some(super::PREFERRED_SIZE)
+        );
+        assert_eq!(icon_size("/usr/share/icons/hicolor/apps"), None);
+    }
+
+    #[test]
+    fn resolves_absolute_paths_of_existing_svg_and_png_files() {
+        let directory = crate::plugin::utils::TestDirectory::new("icons");
+        let svg = directory.path().join("app.svg");
+        let png = directory.path().join("app.png");
+        let xpm = directory.path().join("app.xpm");
+        for path in [&svg, &png, &xpm] {
+            std::fs::write(path, "icon").unwrap();
+        }
+        let mut icons = super::IconLookup::new();
+        let mut find = |path: &std::path::Path| icons.find(path.to_str().unwrap());
+
+        assert_eq!(find(&svg), Some(svg.clone()));
+        assert_eq!(find(&png), Some(png.clone()));
+        assert_eq!(find(&xpm), None);
+        assert_eq!(find(&directory.path().join("missing.png")), None);
+    }
+
+    #[test]
+    fn cache_keys_change_with_the_icon_content() {
+        assert_eq!(
+            super::cache_key(b"png".as_slice()),
+            super::cache_key(b"png".as_slice())
+        );
+        assert_ne!(
+            super::cache_key(b"png".as_slice()),
+            super::cache_key(b"pnh".as_slice())
+        );
+    }
+
+    #[test]
+    fn cached_icons_are_named_after_the_icon_and_its_version() {
+        assert_eq!(
+            super::cached_icon_file_name("/opt/app/icon.png", "00ff"),
+            "_opt_app_icon.png-00ff.png"
+        );
+    }
+
+    #[test]
+    fn other_images_are_not_cached() {
+        assert!(super::cache_png("favicon", b"<svg></svg>").is_err());
+    }
+}
@@ -1,8 +1,14 @@
 pub mod applications;
 pub mod brave;
 pub mod clock;
+pub mod command;
+pub mod external;
 pub mod git_repositories;
+pub mod icons;
+pub mod registry;
 pub mod resource_monitor;
+pub mod script;
+pub mod supervisor;
 pub mod sway_windows;
 pub mod system;
 pub mod utils;
@@ -0,0 +1,300 @@
+/// A built-in plugin. Adding a plugin to [`PLUGINS`] is all it takes to spawn
+/// it, read its settings and list it in the documentation.
+pub struct Registration {
+    /// The id of the plugin, as used by `plugin:` filters, prefixes and frecency.
+    pub id: &'static str,
+    /// The key of the plugin settings in the `plugin` section of the config.
+    pub settings_key: &'static str,
+    pub description: &'static str,
+    /// Deserializes the settings specific to the plugin and spawns it.
+    pub spawn: fn(
+        &crate::settings::BuiltinPluginSettings,
+    ) -> anyhow::Result<iced::Subscription<crate::Message>>,
+}
+
+pub static PLUGINS: &[Registration] = &[
+    Registration {
+        id: "applications",
+        settings_key: "applications",
+        description: "Search for installed applications and launch them.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::applications::ApplicationsPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "brave_bookmarks",
+        settings_key: "brave_bookmarks",
+        description: "Open brave bookmarks in new tabs.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::brave::bookmarks::BookmarksPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "brave_progressive_web_apps",
+        settings_key: "brave_progressive_web_apps",
+        description: "Open the brave bookmarks in the 'Progressive Web Apps' folder in app mode.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::brave::progressive_web_apps::ProgressiveWebAppsPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "brave_history",
+        settings_key: "brave_history",
+        description: "Open the brave history in new tabs.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::brave::history::HistoryPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "clock",
+        settings_key: "clock",
+        description: "Display current date and time.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::clock::ClockPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "git_repositories",
+        settings_key: "git_repositories",
+        description: "List git repositories in your home directory and open a terminal and an editor in them.",
+        spawn: |settings| {
+            let git_settings: crate::settings::GitRepositoriesPluginSettings =
+                settings.options()?;
+            Ok(crate::plugin::utils::spawn_with_id(
+                std::any::TypeId::of::<crate::plugin::git_repositories::GitRepositoriesPlugin>(),
+                move || {
+                    crate::plugin::git_repositories::GitRepositoriesPlugin::new(
+                        git_settings.clone(),
+                    )
+                },
+            ))
+        },
+    },
+    Registration {
+        id: "resource_monitor_battery",
+        settings_key: "resource_monitor_battery",
+        description: "Display the battery state.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::resource_monitor::battery::BatteryPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "resource_monitor_cpu",
+        settings_key: "resource_monitor_cpu",
+        description: "Display current cpu usage.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::resource_monitor::cpu::CpuPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "resource_monitor_disks",
+        settings_key: "resource_monitor_disks",
+        description: "Display current disk usage.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::resource_monitor::disks::DisksPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "resource_monitor_memory",
+        settings_key: "resource_monitor_memory",
+        description: "Display current ram usage.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::resource_monitor::memory::MemoryPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "system",
+        settings_key: "system",
+        description: "Lock, sleep, restart or shutdown your system.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::system::SystemPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "wifi",
+        settings_key: "wifi",
+        description: "List nearby wifi networks and connect to them.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::wifi::WifiPlugin::new,
+            ))
+        },
+    },
+    Registration {
+        id: "sway_windows",
+        settings_key: "sway_windows",
+        description: "Search for open sway windows and switch between them.",
+        spawn: |_| {
+            Ok(crate::plugin::utils::spawn(
+                crate::plugin::sway_windows::SwayWindowsPlugin::new,
+            ))
+        },
+    },
+];
+
+/// Former plugin ids that are still accepted, with the id replacing them.
+static DEPRECATED_IDS: &[(&str, &str)] = &[("sway-windows", "sway_windows")];
+
+/// The current id of a plugin, warning about deprecated ids.
+pub fn current_id(plugin_id: &str) -> &str {
+    match DEPRECATED_IDS
+        .iter()
+        .find(|(deprecated_id, _)| *deprecated_id == plugin_id)
+    {
+        Some((deprecated_id, id)) => {
+            log::warn!("The plugin id '{deprecated_id}' is deprecated, use '{id}' instead.");
+            id
+        }
+        None => plugin_id,
+    }
+}
+
+pub fn find(plugin_id: &str) -> Option<&'static Registration> {
+    PLUGINS
+        .iter()
+        .find(|registration| registration.id == plugin_id)
+}
+
+/// Spawns the enabled built-in plugins.
+pub fn spawn(
+    settings: &crate::settings::PluginSettings,
+) -> Vec<iced::Subscription<crate::Message>> {
+    PLUGINS
+        .iter()
+        .filter_map(|registration| {
+            let plugin_settings = settings.builtin(registration.settings_key);
+            if !plugin_settings.enable {
+                return None;
+            }
+            match (registration.spawn)(&plugin_settings) {
+                Ok(subscription) => Some(subscription),
+                Err(error) => {
+                    log::error!(target: registration.id, "{:?}", error);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Checks the settings of all built-in plugins, so mistakes are reported when
+/// the config is read. Spawning only describes the subscription without
+/// running the plugin, so it parses the settings exactly like the app does.
+pub fn check_settings(settings: &crate::settings::PluginSettings) -> anyhow::Result<()> {
+    for settings_key in settings.builtin.keys() {
+        if !PLUGINS
+            .iter()
+            .any(|registration| registration.settings_key == settings_key)
+        {
+            log::warn!("Ignoring settings of unknown plugin '{settings_key}'.");
+        }
+    }
+
+    for registration in PLUGINS {
+        (registration.spawn)(&settings.builtin(registration.settings_key))
+            .map(drop)
+            .map_err(|error| {
+                error.context(format!(
+                    "Invalid settings for plugin '{}'.",
+                    registration.settings_key
+                ))
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Lists the built-in plugins with their settings key and description.
+pub fn documentation() -> String {
+    PLUGINS
+        .iter()
+        .map(|registration| {
+            format!(
+                "{:<28}{}\n",
+                registration.settings_key, registration.description
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn readme_documents_every_plugin() {
+        let readme = include_str!("../../../README.md");
+        for registration in super::PLUGINS {
+            assert!(
+                readme.contains(&format!("  {}:\n", registration.settings_key)),
+                "README.md lacks the settings of plugin '{}'",
+                registration.settings_key
+            );
+        }
+    }
+
+    #[test]
+    fn home_manager_module_configures_every_plugin() {
+        let module = include_str!("../../../home-manager-module.nix");
+        for registration in super::PLUGINS {
+            assert!(
+                module.contains(&format!("      {} = {{\n", registration.settings_key)),
+                "home-manager-module.nix lacks the options of plugin '{}'",
+                registration.settings_key
+            );
+        }
+    }
+
+    #[test]
+    fn plugin_ids_are_their_settings_keys() {
+        for registration in super::PLUGINS {
+            assert_eq!(registration.id, registration.settings_key);
+        }
+    }
+
+    #[test]
+    fn deprecated_ids_refer_to_current_plugins() {
+        for (deprecated_id, id) in super::DEPRECATED_IDS {
+            assert!(super::find(deprecated_id).is_none());
+            assert!(super::find(id).is_some());
+            assert_eq!(super::current_id(deprecated_id), *id);
+        }
+        assert_eq!(super::current_id("clock"), "clock");
+    }
+
+    #[test]
+    fn plugin_ids_and_settings_keys_are_unique() {
+        let mut ids: Vec<&str> = super::PLUGINS
+            .iter()
+            .map(|registration| registration.id)
+            .collect();
+        let mut settings_keys: Vec<&str> = super::PLUGINS
+            .iter()
+            .map(|registration| registration.settings_key)
+            .collect();
+        ids.sort();
+        ids.dedup();
+        settings_keys.sort();
+        settings_keys.dedup();
+        assert_eq!(ids.len(), super::PLUGINS.len());
+        assert_eq!(settings_keys.len(), super::PLUGINS.len());
+    }
+}
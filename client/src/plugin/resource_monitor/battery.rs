@@ -6,20 +6,27 @@ pub struct BatteryPlugin {
     entries: Vec<crate::model::Entry>,
 }
 
+impl BatteryPlugin {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+#[async_trait::async_trait]
 impl Plugin for BatteryPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "resource_monitor_battery"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         14
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰁼 Battery"
     }
 
-    fn update_timeout() -> Option<std::time::Duration> {
+    fn update_timeout(&self) -> Option<std::time::Duration> {
         Some(std::time::Duration::from_secs(2))
     }
 
@@ -27,50 +34,57 @@ impl Plugin for BatteryPlugin {
         self.entries.clone()
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
-
-        let batteries = battery::Manager::new()
-            .context("Failed to create battery manager.")?
-            .batteries()
-            .context("Failed to list batteries using the battery manager.")?;
-
-        for battery_result in batteries {
-            let battery =
-                battery_result.context("Failed to get battery using the batteries iterator.")?;
-
-            let state_of_charge = battery.state_of_charge() * 100.0;
-
-            let time_to_full_remaining = match battery.time_to_full() {
-                Some(time_to_full) => to_display(time_to_full),
-                None => String::new(),
-            };
-
-            let time_to_empty_remaining = match battery.time_to_empty() {
-                Some(time_to_empty) => to_display(time_to_empty),
-                None => String::new(),
-            };
-
-            let title = format!(
-                "{state_of_charge:.0?}% – {state}{time_to_full_remaining}{time_to_empty_remaining}",
-                state = battery.state(),
-            );
-
-            self.entries.push(crate::model::Entry {
-                id: String::from("battery"),
-                title,
-                action: String::from(""),
-                meta: String::from("Resource Monitor Battery"),
-                command: None,
-            });
-        }
-
+        self.entries = crate::plugin::utils::spawn_blocking(read_battery_entries).await?;
         Ok(())
     }
+}
 
-    fn new() -> Self {
-        Self { entries: vec![] }
+fn read_battery_entries() -> anyhow::Result<Vec<crate::model::Entry>> {
+    let mut entries = vec![];
+
+    let batteries = battery::Manager::new()
+        .context("Failed to create battery manager.")?
+        .batteries()
+        .context("Failed to list batteries using the battery manager.")?;
+
+    for battery_result in batteries {
+        let battery =
+            battery_result.context("Failed to get battery using the batteries iterator.")?;
+
+        let state_of_charge = battery.state_of_charge() * 100.0;
+
+        let time_to_full_remaining = match battery.time_to_full() {
+            Some(time_to_full) => to_display(time_to_full),
+            None => String::new(),
+        };
+
+        let time_to_empty_remaining = match battery.time_to_empty() {
+            Some(time_to_empty) => to_display(time_to_empty),
+            None => String::new(),
+        };
+
+        let title = format!(
+            "{state_of_charge:.0?}% – {state}{time_to_full_remaining}{time_to_empty_remaining}",
+            state = battery.state(),
+        );
+
+        entries.push(crate::model::Entry {
+            id: String::from("battery"),
+            title: title.clone(),
+            actions: vec![],
+            meta: String::from("Resource Monitor Battery"),
+            command: None,
+            kind: Some(crate::model::EntryKind::Text(title)),
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        });
     }
+
+    Ok(entries)
 }
 
 fn to_display(time_to_empty: battery::units::Time) -> String {
@@ -3,56 +3,122 @@ use crate::plugin::utils::Plugin;
 pub struct CpuPlugin {
     sysinfo: sysinfo::System,
     entries: Vec<crate::model::Entry>,
+    // The latest usages in percent of every core by name, oldest first.
+    usage_history: std::collections::HashMap<String, std::collections::VecDeque<f32>>,
 }
 
+const USAGE_HISTORY_LENGTH: usize = 30;
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+impl CpuPlugin {
+    pub fn new() -> Self {
+        Self {
+            sysinfo: sysinfo::System::new_all(),
+            entries: vec![],
+            usage_history: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Draws the usages as a line of bars, e.g. `▁▃▇█▅`.
+    fn sparkline(usages: &std::collections::VecDeque<f32>) -> String {
+        usages
+            .iter()
+            .map(|usage| {
+                let level = (usage / 100. * 7.).round().clamp(0., 7.) as usize;
+                SPARKLINE_BARS[level]
+            })
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
 impl Plugin for CpuPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "resource_monitor_cpu"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         13
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰍛 CPU"
     }
 
-    fn update_timeout() -> Option<std::time::Duration> {
+    fn update_timeout(&self) -> Option<std::time::Duration> {
         Some(std::time::Duration::from_secs(2))
     }
 
+    fn has_preview(&self) -> bool {
+        true
+    }
+
     fn entries(&self) -> Vec<crate::model::Entry> {
         self.entries.clone()
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
-        self.sysinfo.refresh_cpu();
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
+        let mut sysinfo = std::mem::take(&mut self.sysinfo);
+        self.sysinfo = crate::plugin::utils::spawn_blocking(move || {
+            sysinfo.refresh_cpu();
+            Ok(sysinfo)
+        })
+        .await?;
 
         self.entries.clear();
         for cpu_core in self.sysinfo.cpus() {
+            let usage_history = self
+                .usage_history
+                .entry(cpu_core.name().to_string())
+                .or_default();
+            usage_history.push_back(cpu_core.cpu_usage());
+            if usage_history.len() > USAGE_HISTORY_LENGTH {
+                usage_history.pop_front();
+            }
+
+            let title = format!(
+                "{}: {}% {}MHz",
+                cpu_core.name(),
+                cpu_core.cpu_usage() as i32,
+                cpu_core.frequency()
+            );
             self.entries.push(crate::model::Entry {
                 id: cpu_core.name().to_string(),
-                title: format!(
-                    "{}: {}% {}MHz",
-                    cpu_core.name(),
-                    cpu_core.cpu_usage() as i32,
-                    cpu_core.frequency()
-                ),
-                action: String::from(""),
+                title: title.clone(),
+                actions: vec![],
                 meta: String::from("Resource Monitor CPU"),
                 command: None,
+                kind: Some(crate::model::EntryKind::Text(title)),
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             });
         }
 
         Ok(())
     }
 
-    fn new() -> Self {
-        Self {
-            sysinfo: sysinfo::System::new_all(),
-            entries: vec![],
-        }
+    async fn preview(
+        &mut self,
+        _entry: &crate::model::Entry,
+    ) -> anyhow::Result<Option<crate::model::Preview>> {
+        let fields = self
+            .sysinfo
+            .cpus()
+            .iter()
+            .filter_map(|cpu_core| {
+                let usage_history = self.usage_history.get(cpu_core.name())?;
+                Some((
+                    format!("{}: {}%", cpu_core.name(), cpu_core.cpu_usage() as i32),
+                    Self::sparkline(usage_history),
+                ))
+            })
+            .collect();
+        Ok(Some(crate::model::Preview {
+            fields,
+            text: String::new(),
+        }))
     }
 }
 
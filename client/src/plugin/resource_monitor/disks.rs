@@ -6,20 +6,30 @@ pub struct DisksPlugin {
     entries: Vec<crate::model::Entry>,
 }
 
+impl DisksPlugin {
+    pub fn new() -> Self {
+        Self {
+            disks: sysinfo::Disks::new(),
+            entries: vec![],
+        }
+    }
+}
+
+#[async_trait::async_trait]
 impl Plugin for DisksPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "resource_monitor_disks"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         12
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󱛟 Disks"
     }
 
-    fn update_timeout() -> Option<std::time::Duration> {
+    fn update_timeout(&self) -> Option<std::time::Duration> {
         Some(std::time::Duration::from_secs(2))
     }
 
@@ -27,8 +37,14 @@ impl Plugin for DisksPlugin {
         self.entries.clone()
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
-        self.disks.refresh_list();
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
+        // Listing disks blocks while network mounts are unreachable.
+        let mut disks = std::mem::take(&mut self.disks);
+        self.disks = crate::plugin::utils::spawn_blocking(move || {
+            disks.refresh_list();
+            Ok(disks)
+        })
+        .await?;
         self.entries.clear();
 
         for disk in &self.disks {
@@ -48,24 +64,23 @@ impl Plugin for DisksPlugin {
                 &mount_point, perentage_used, used_space_in_gb, total_space_in_gb
             );
 
+            let kind = crate::model::EntryKind::Path(std::path::PathBuf::from(&mount_point));
             self.entries.push(crate::model::Entry {
                 id: mount_point,
                 title,
-                action: String::from(""),
+                actions: vec![],
                 meta: String::from("Resource Monitor Disks"),
                 command: None,
+                kind: Some(kind),
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             });
         }
 
         Ok(())
     }
-
-    fn new() -> Self {
-        Self {
-            disks: sysinfo::Disks::new(),
-            entries: vec![],
-        }
-    }
 }
 
 impl Default for DisksPlugin {
@@ -5,20 +5,30 @@ pub struct MemoryPlugin {
     entries: Vec<crate::model::Entry>,
 }
 
+impl MemoryPlugin {
+    pub fn new() -> Self {
+        Self {
+            sysinfo: sysinfo::System::new_all(),
+            entries: vec![],
+        }
+    }
+}
+
+#[async_trait::async_trait]
 impl Plugin for MemoryPlugin {
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "resource_monitor_memory"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         11
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󱓱 Memory"
     }
 
-    fn update_timeout() -> Option<std::time::Duration> {
+    fn update_timeout(&self) -> Option<std::time::Duration> {
         Some(std::time::Duration::from_secs(2))
     }
 
@@ -26,8 +36,13 @@ impl Plugin for MemoryPlugin {
         self.entries.clone()
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
-        self.sysinfo.refresh_memory();
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
+        let mut sysinfo = std::mem::take(&mut self.sysinfo);
+        self.sysinfo = crate::plugin::utils::spawn_blocking(move || {
+            sysinfo.refresh_memory();
+            Ok(sysinfo)
+        })
+        .await?;
         self.entries.clear();
 
         let perentage_used = 100 * self.sysinfo.used_memory() / self.sysinfo.total_memory();
@@ -41,21 +56,19 @@ impl Plugin for MemoryPlugin {
 
         self.entries.push(crate::model::Entry {
             id: String::from("memory"),
-            title,
-            action: String::from(""),
+            title: title.clone(),
+            actions: vec![],
             meta: String::from("Resource Monitor Memory RAM"),
             command: None,
+            kind: Some(crate::model::EntryKind::Text(title)),
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
         });
 
         Ok(())
     }
-
-    fn new() -> Self {
-        Self {
-            sysinfo: sysinfo::System::new_all(),
-            entries: vec![],
-        }
-    }
 }
 
 impl Default for MemoryPlugin {
@@ -0,0 +1,516 @@
+use crate::plugin::utils::Plugin;
+use anyhow::Context;
+
+const SCRIPT_EXTENSION: &str = "rhai";
+
+// Scripts cannot access files or processes themselves and are aborted when
+// they run away. They are trusted nonetheless: the commands of their entries
+// and the commands returned by `activate` are run as they are.
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 64 * 1024;
+const MAX_ARRAY_SIZE: usize = 100_000;
+const MAX_MAP_SIZE: usize = 1_000;
+
+/// Finds the `.rhai` scripts in the `plugins` directory of the centerpiece
+/// config directory. Returns the plugin id, i.e. the file name without
+/// extension, together with the script path.
+pub fn discover_scripts(
+    plugin_settings: &crate::settings::PluginSettings,
+) -> Vec<(String, std::path::PathBuf)> {
+    match crate::plugin::utils::centerpiece_config_directory() {
+        Ok(config_directory) => scripts_in(
+            &std::path::Path::new(&config_directory).join("plugins"),
+            plugin_settings,
+        ),
+        Err(error) => {
+            log::warn!("Unable to find script plugins directory: {:?}", error);
+            vec![]
+        }
+    }
+}
+
+/// The scripts in the directory, skipping scripts whose id is taken by a
+/// built-in, external or command plugin.
+fn scripts_in(
+    directory: &std::path::Path,
+    plugin_settings: &crate::settings::PluginSettings,
+) -> Vec<(String, std::path::PathBuf)> {
+    let Ok(directory_entries) = std::fs::read_dir(directory) else {
+        return vec![];
+    };
+
+    let mut scripts: Vec<(String, std::path::PathBuf)> = directory_entries
+        .filter_map(|directory_entry| directory_entry.ok())
+        .map(|directory_entry| directory_entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.extension().and_then(|extension| extension.to_str())
+                    == Some(SCRIPT_EXTENSION)
+        })
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            Some((id, path))
+        })
+        .filter(|(id, path)| {
+            let is_taken = crate::plugin::registry::find(id).is_some()
+                || plugin_settings
+                    .external
+                    .iter()
+                    .any(|external| &external.id == id)
+                || plugin_settings
+                    .commands
+                    .iter()
+                    .any(|command| &command.id == id);
+            if is_taken {
+                log::error!(
+                    "Ignoring script '{}', the plugin id '{}' is taken by another plugin. Rename the script to use it.",
+                    path.display(),
+                    id
+                );
+            }
+            !is_taken
+        })
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+pub fn spawn(
+    id: String,
+    path: std::path::PathBuf,
+    settings: crate::settings::ScriptPluginSettings,
+) -> iced::Subscription<crate::Message> {
+    crate::plugin::utils::spawn_with_id(path.clone(), move || {
+        ScriptPlugin::new(id.clone(), path.clone(), settings.clone())
+    })
+}
+
+/// A plugin defined by a Rhai script. The script defines `entries()` and
+/// optionally `search(query)` and `activate(entry)` or `activate(entry, action)`.
+pub struct ScriptPlugin {
+    id: String,
+    title: String,
+    priority: u32,
+    update_timeout: Option<std::time::Duration>,
+    path: std::path::PathBuf,
+    // Shared with the blocking thread pool, which runs the scripts.
+    engine: std::sync::Arc<rhai::Engine>,
+    script: Option<std::sync::Arc<rhai::AST>>,
+    entries: Vec<crate::model::Entry>,
+}
+
+impl ScriptPlugin {
+    pub fn new(
+        id: String,
+        path: std::path::PathBuf,
+        settings: crate::settings::ScriptPluginSettings,
+    ) -> Self {
+        let mut engine = rhai::Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_ARRAY_SIZE)
+            .set_max_map_size(MAX_MAP_SIZE)
+            .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+
+        let log_target = id.clone();
+        engine.on_print(move |text| log::info!(target: log_target.as_str(), "{}", text));
+
+        Self {
+            title: settings.title.unwrap_or_else(|| id.clone()),
+            id,
+            priority: settings.priority,
+            update_timeout: settings.update_interval.map(std::time::Duration::from_secs),
+            path,
+            engine: std::sync::Arc::new(engine),
+            script: None,
+            entries: vec![],
+        }
+    }
+
+    async fn script(&mut self) -> anyhow::Result<std::sync::Arc<rhai::AST>> {
+        if let Some(script) = &self.script {
+            return Ok(script.clone());
+        }
+
+        let engine = self.engine.clone();
+        let path = self.path.clone();
+        let script = crate::plugin::utils::spawn_blocking(move || {
+            let source = std::fs::read_to_string(&path)
+                .context(format!("Error while reading script '{}'.", path.display()))?;
+            engine.compile(source).context(format!(
+                "Error while compiling script '{}'.",
+                path.display()
+            ))
+        })
+        .await?;
+        let script = std::sync::Arc::new(script);
+        self.script = Some(script.clone());
+        Ok(script)
+    }
+
+    async fn defines_function(
+        &mut self,
+        name: &str,
+        parameter_count: usize,
+    ) -> anyhow::Result<bool> {
+        Ok(self
+            .script()
+            .await?
+            .iter_functions()
+            .any(|function| function.name == name && function.params.len() == parameter_count))
+    }
+
+    /// Calls the function of the script on the blocking thread pool, since a
+    /// script may run for up to `MAX_OPERATIONS` operations.
+    async fn call(
+        &mut self,
+        name: &str,
+        arguments: impl rhai::FuncArgs + std::marker::Send + 'static,
+    ) -> anyhow::Result<rhai::Dynamic> {
+        let script = self.script().await?;
+        let engine = self.engine.clone();
+        let context = format!(
+            "Error while calling '{name}' of script '{}'.",
+            self.path.display()
+        );
+        let name = String::from(name);
+        crate::plugin::utils::spawn_blocking(move || {
+            engine
+                .call_fn::<rhai::Dynamic>(&mut rhai::Scope::new(), &script, name, arguments)
+                .context(context)
+        })
+        .await
+    }
+
+    /// The command returned by `activate` of the script, the command of the
+    /// entry if the script does not define `activate`.
+    async fn activation_command(
+        &mut self,
+        entry: &crate::model::Entry,
+        action: &str,
+    ) -> anyhow::Result<Option<Vec<String>>> {
+        let script_entry =
+            rhai::serde::to_dynamic(crate::plugin::external::ExternalEntry::from(entry.clone()))
+                .context("Failed to pass entry to script.")?;
+        let result = if self.defines_function("activate", 2).await? {
+            Some(
+                self.call("activate", (script_entry, String::from(action)))
+                    .await?,
+            )
+        } else if self.defines_function("activate", 1).await? {
+            Some(self.call("activate", (script_entry,)).await?)
+        } else {
+            None
+        };
+        let command = match result {
+            Some(result) if result.is_unit() => None,
+            Some(result) => Some(rhai::serde::from_dynamic::<Vec<String>>(&result).context(
+                format!(
+                    "The result of 'activate' of script '{}' is no command.",
+                    self.path.display()
+                ),
+            )?),
+            None => entry.command.clone(),
+        };
+        Ok(command.filter(|command| !command.is_empty()))
+    }
+
+    async fn call_for_entries(
+        &mut self,
+        name: &str,
+        arguments: impl rhai::FuncArgs + std::marker::Send + 'static,
+    ) -> anyhow::Result<Vec<crate::model::Entry>> {
+        let result = self.call(name, arguments).await?;
+        let script_entries: Vec<crate::plugin::external::ExternalEntry> =
+            rhai::serde::from_dynamic(&result).context(format!(
+                "The result of '{name}' of script '{}' is no array of entries.",
+                self.path.display()
+            ))?;
+        Ok(script_entries.into_iter().map(Into::into).collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for ScriptPlugin {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn update_timeout(&self) -> Option<std::time::Duration> {
+        self.update_timeout
+    }
+
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
+        self.entries = self.call_for_entries("entries", ()).await?;
+        Ok(())
+    }
+
+    fn entries(&self) -> Vec<crate::model::Entry> {
+        self.entries.clone()
+    }
+
+    async fn results(
+        &mut self,
+        query: &crate::search::query::Query,
+    ) -> anyhow::Result<Vec<crate::model::Entry>> {
+        // Scripts defining `search` produce and rank their entries themselves.
+        match self.defines_function("search", 1).await? {
+            true => self.call_for_entries("search", (query.text.clone(),)).await,
+            false => {
+                crate::plugin::utils::spawn_search(&self.id, self.entries(), query.clone()).await
+            }
+        }
+    }
+
+    async fn activate(
+        &mut self,
+        entry: crate::model::Entry,
+        action: &str,
+        plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
+    ) -> anyhow::Result<()> {
+        if let Some(command) = self.activation_command(&entry, action).await? {
+            std::process::Command::new(&command[0])
+                .args(&command[1..])
+                .spawn()
+                .context(format!(
+                    "Failed to run command while activating entry with id '{}'.",
+                    entry.id
+                ))?;
+        }
+
+        plugin_channel_out
+            .try_send(crate::Message::Exit)
+            .context(format!(
+                "Failed to send message to exit application while activating entry with id '{}'.",
+                entry.id
+            ))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::utils::Plugin;
+
+    /// A script plugin whose script is removed once the plugin is dropped.
+    struct TestScript {
+        plugin: super::ScriptPlugin,
+        _directory: crate::plugin::utils::TestDirectory,
+    }
+
+    impl std::ops::Deref for TestScript {
+        type Target = super::ScriptPlugin;
+
+        fn deref(&self) -> &Self::Target {
+            &self.plugin
+        }
+    }
+
+    impl std::ops::DerefMut for TestScript {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.plugin
+        }
+    }
+
+    fn script_plugin(name: &str, source: &str) -> TestScript {
+        let directory = crate::plugin::utils::TestDirectory::new(&format!("script-{name}"));
+        let path = directory.path().join(format!("{name}.rhai"));
+        std::fs::write(&path, source).unwrap();
+        TestScript {
+            plugin: super::ScriptPlugin::new(
+                String::from(name),
+                path,
+                crate::settings::ScriptPluginSettings::default(),
+            ),
+            _directory: directory,
+        }
+    }
+
+    fn activation_command(
+        plugin: &mut super::ScriptPlugin,
+        entry: &crate::model::Entry,
+        action: &str,
+    ) -> Option<Vec<String>> {
+        iced::futures::executor::block_on(plugin.activation_command(entry, action)).unwrap()
+    }
+
+    fn titles(entries: &[crate::model::Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.title.as_str()).collect()
+    }
+
+    const DOCS_SCRIPT: &str = r#"
+        fn entries() {
+            [
+                #{ id: "std", title: "Rust standard library", command: ["xdg-open", "https://doc.rust-lang.org/std"] },
+                #{ id: "rhai", title: "Rhai book", action: "read", secondary_actions: ["copy"] },
+            ]
+        }
+    "#;
+
+    #[test]
+    fn loads_the_entries_of_a_script() {
+        let mut plugin = script_plugin("docs", DOCS_SCRIPT);
+        iced::futures::executor::block_on(plugin.update_entries()).unwrap();
+
+        let entries = plugin.entries();
+        assert_eq!(titles(&entries), vec!["Rust standard library", "Rhai book"]);
+        assert_eq!(entries[0].primary_action(), "open");
+        assert_eq!(
+            entries[1].actions,
+            vec![
+                crate::model::Action::new("read", "read"),
+                crate::model::Action::new("copy", "copy")
+            ]
+        );
+    }
+
+    #[test]
+    fn searches_the_entries_unless_the_script_searches_itself() {
+        let mut plugin = script_plugin("docs_search", DOCS_SCRIPT);
+        iced::futures::executor::block_on(plugin.update_entries()).unwrap();
+        let results = iced::futures::executor::block_on(
+            plugin.results(&crate::search::query::Query::parse("rhai")),
+        )
+        .unwrap();
+        assert_eq!(titles(&results), vec!["Rhai book"]);
+
+        let mut plugin = script_plugin(
+            "echo",
+            r#"
+                fn entries() { [] }
+                fn search(query) { [#{ id: query, title: `Echo ${query}` }] }
+            "#,
+        );
+        let results = iced::futures::executor::block_on(
+            plugin.results(&crate::search::query::Query::parse("hello")),
+        )
+        .unwrap();
+        assert_eq!(titles(&results), vec!["Echo hello"]);
+    }
+
+    #[test]
+    fn applies_the_kind_filter_to_the_results_of_scripts() {
+        let mut plugin = script_plugin(
+            "kinds",
+            r#"
+                fn entries() { [] }
+                fn search(query) {
+                    [
+                        #{ id: "docs", title: "Docs", kind: #{ url: "https://docs.rs" } },
+                        #{ id: "notes", title: "Notes", kind: #{ path: "/home/me/notes" } },
+                        #{ id: "plain", title: "Plain" },
+                    ]
+                }
+            "#,
+        );
+        let (mut plugin_channel_out, mut app_channel_in) = iced::futures::channel::mpsc::channel(1);
+
+        let shown_entries = iced::futures::executor::block_on(plugin.search(
+            &crate::search::query::Query::parse("kind:url docs"),
+            &mut plugin_channel_out,
+        ))
+        .unwrap();
+
+        assert!(shown_entries.is_empty());
+        match app_channel_in.try_next() {
+            Ok(Some(crate::Message::UpdateEntries(_, _, entries))) => {
+                assert_eq!(titles(&entries), vec!["Docs"]);
+            }
+            _ => panic!("The results were not sent."),
+        }
+    }
+
+    #[test]
+    fn activates_entries_through_the_script() {
+        let mut plugin = script_plugin("docs_activate", DOCS_SCRIPT);
+        iced::futures::executor::block_on(plugin.update_entries()).unwrap();
+        let entries = plugin.entries();
+        assert_eq!(
+            activation_command(&mut plugin, &entries[0], "open"),
+            Some(vec![
+                String::from("xdg-open"),
+                String::from("https://doc.rust-lang.org/std")
+            ])
+        );
+        assert_eq!(activation_command(&mut plugin, &entries[1], "read"), None);
+
+        let mut plugin = script_plugin(
+            "open",
+            r#"
+                fn entries() { [#{ id: "notes", title: "Notes" }] }
+                fn activate(entry) { ["xdg-open", entry.id] }
+            "#,
+        );
+        iced::futures::executor::block_on(plugin.update_entries()).unwrap();
+        let entry = plugin.entries().remove(0);
+        assert_eq!(
+            activation_command(&mut plugin, &entry, "open"),
+            Some(vec![String::from("xdg-open"), String::from("notes")])
+        );
+
+        let mut plugin = script_plugin(
+            "actions",
+            r#"
+                fn entries() { [#{ id: "notes", title: "Notes" }] }
+                fn activate(entry, action) {
+                    if action == "copy" { ["wl-copy", entry.id] } else { () }
+                }
+            "#,
+        );
+        iced::futures::executor::block_on(plugin.update_entries()).unwrap();
+        let entry = plugin.entries().remove(0);
+        assert_eq!(
+            activation_command(&mut plugin, &entry, "copy"),
+            Some(vec![String::from("wl-copy"), String::from("notes")])
+        );
+        assert_eq!(activation_command(&mut plugin, &entry, "open"), None);
+    }
+
+    #[test]
+    fn aborts_runaway_scripts() {
+        let mut plugin = script_plugin("loop", "fn entries() { loop {} }");
+        assert!(iced::futures::executor::block_on(plugin.update_entries()).is_err());
+
+        let mut plugin = script_plugin("recursion", "fn entries() { entries() }");
+        assert!(iced::futures::executor::block_on(plugin.update_entries()).is_err());
+    }
+
+    #[test]
+    fn rejects_eval() {
+        let mut plugin = script_plugin("eval", r#"fn entries() { eval("[]") }"#);
+        assert!(iced::futures::executor::block_on(plugin.update_entries()).is_err());
+    }
+
+    #[test]
+    fn skips_scripts_with_taken_ids() {
+        let test_directory = crate::plugin::utils::TestDirectory::new("script-discovery");
+        let directory = test_directory.path();
+        for name in ["notes.rhai", "clock.rhai", "files.rhai", "readme.md"] {
+            std::fs::write(directory.join(name), "fn entries() { [] }").unwrap();
+        }
+        let plugin_settings: crate::settings::PluginSettings = serde_yaml::from_str(
+            "commands:\n  - id: files\n    title: Files\n    list: [ls]\n    activate: [xdg-open, \"{line}\"]\n",
+        )
+        .unwrap();
+
+        let scripts = super::scripts_in(directory, &plugin_settings);
+
+        assert_eq!(
+            scripts,
+            vec![(String::from("notes"), directory.join("notes.rhai"))]
+        );
+    }
+}
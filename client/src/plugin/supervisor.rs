@@ -0,0 +1,200 @@
+use iced::futures::FutureExt;
+
+const INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+const MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+// A plugin running longer than this before failing starts over with the
+// initial backoff.
+const STABLE_RUN: std::time::Duration = std::time::Duration::from_secs(60);
+
+pub fn panic_message(panic: &(dyn std::any::Any + std::marker::Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| String::from(*message))
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown cause"))
+}
+
+/// Restarts a failed plugin with exponential backoff and reports its
+/// failures to the app instead of taking down the subscription.
+pub struct Supervisor {
+    plugin_id: String,
+    failures: u32,
+    last_error: Option<String>,
+    started_at: std::time::Instant,
+}
+
+impl Supervisor {
+    pub fn new(plugin_id: &str) -> Self {
+        Self {
+            plugin_id: String::from(plugin_id),
+            failures: 0,
+            last_error: None,
+            started_at: std::time::Instant::now(),
+        }
+    }
+
+    /// Runs the plugin main loop until it fails, catching panics as errors.
+    pub async fn run(
+        &mut self,
+        main: impl std::future::Future<Output = anyhow::Result<()>> + std::marker::Send,
+    ) -> anyhow::Error {
+        self.started_at = std::time::Instant::now();
+
+        match std::panic::AssertUnwindSafe(main).catch_unwind().await {
+            Ok(Ok(())) => anyhow::anyhow!("Plugin stopped unexpectedly."),
+            Ok(Err(error)) => error,
+            Err(panic) => anyhow::anyhow!("Plugin panicked: {}", panic_message(&*panic)),
+        }
+    }
+
+    /// Reports the error to the app and waits before the plugin may restart.
+    pub async fn recover(
+        &mut self,
+        error: anyhow::Error,
+        plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
+    ) {
+        let error_message = format!("{:#}", error);
+        let (backoff, is_repeated) = self.record_failure(self.started_at.elapsed(), &error_message);
+
+        // Repeated failures with the same cause are only logged once.
+        if !is_repeated {
+            log::error!(
+                target: self.plugin_id.as_str(),
+                "{:?}", error,
+            );
+        } else {
+            log::debug!(
+                target: self.plugin_id.as_str(),
+                "Failed again ({} times), restarting in {:?}: {}", self.failures, backoff, error_message,
+            );
+        }
+
+        // The app drops the entries of failed plugins. The outermost context is
+        // shown to the user, the whole chain is logged.
+        let _ = plugin_channel_out.try_send(crate::Message::UpdatePluginStatus(
+            self.plugin_id.clone(),
+            crate::model::PluginStatus::Error(error.to_string()),
+        ));
+
+        async_std::task::sleep(backoff).await;
+
+        // The restarted plugin loads its entries again, the error is over.
+        let _ = plugin_channel_out.try_send(crate::Message::UpdatePluginStatus(
+            self.plugin_id.clone(),
+            crate::model::PluginStatus::Loading,
+        ));
+    }
+
+    /// Records a failure after the plugin ran for `run_time`. Returns the
+    /// backoff before the restart and whether the failure has the same cause
+    /// as the previous one.
+    fn record_failure(
+        &mut self,
+        run_time: std::time::Duration,
+        error_message: &str,
+    ) -> (std::time::Duration, bool) {
+        if run_time > STABLE_RUN {
+            self.failures = 0;
+        }
+        let backoff = INITIAL_BACKOFF
+            .saturating_mul(2_u32.saturating_pow(self.failures))
+            .min(MAX_BACKOFF);
+        self.failures += 1;
+
+        let is_repeated = self.last_error.as_deref() == Some(error_message);
+        self.last_error = Some(String::from(error_message));
+        (backoff, is_repeated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Supervisor, INITIAL_BACKOFF, MAX_BACKOFF, STABLE_RUN};
+
+    const SHORT_RUN: std::time::Duration = std::time::Duration::from_millis(10);
+
+    fn backoffs(supervisor: &mut Supervisor, count: usize) -> Vec<u64> {
+        (0..count)
+            .map(|_| supervisor.record_failure(SHORT_RUN, "error").0.as_secs())
+            .collect()
+    }
+
+    #[test]
+    fn doubles_the_backoff_up_to_the_maximum() {
+        let mut supervisor = Supervisor::new("test");
+        assert_eq!(
+            backoffs(&mut supervisor, 11),
+            vec![1, 2, 4, 8, 16, 32, 64, 128, 256, 300, 300]
+        );
+        // The exponent keeps growing, the backoff does not overflow.
+        for _ in 0..100 {
+            supervisor.record_failure(SHORT_RUN, "error");
+        }
+        assert_eq!(supervisor.record_failure(SHORT_RUN, "error").0, MAX_BACKOFF);
+    }
+
+    #[test]
+    fn resets_the_backoff_after_a_stable_run() {
+        let mut supervisor = Supervisor::new("test");
+        backoffs(&mut supervisor, 5);
+
+        let stable_run = STABLE_RUN + std::time::Duration::from_secs(1);
+        assert_eq!(
+            supervisor.record_failure(stable_run, "error").0,
+            INITIAL_BACKOFF
+        );
+        assert_eq!(backoffs(&mut supervisor, 2), vec![2, 4]);
+    }
+
+    #[test]
+    fn recognizes_repeated_failures() {
+        let mut supervisor = Supervisor::new("test");
+        assert!(!supervisor.record_failure(SHORT_RUN, "first").1);
+        assert!(supervisor.record_failure(SHORT_RUN, "first").1);
+        assert!(!supervisor.record_failure(SHORT_RUN, "second").1);
+        assert!(!supervisor.record_failure(SHORT_RUN, "first").1);
+    }
+
+    #[test]
+    fn reports_the_error_and_then_the_restart() {
+        let mut supervisor = Supervisor::new("test");
+        let (mut plugin_channel_out, mut app_channel_in) = iced::futures::channel::mpsc::channel(2);
+
+        iced::futures::executor::block_on(
+            supervisor.recover(anyhow::anyhow!("no index"), &mut plugin_channel_out),
+        );
+
+        let mut statuses = vec![];
+        while let Ok(Some(crate::Message::UpdatePluginStatus(plugin_id, status))) =
+            app_channel_in.try_next()
+        {
+            assert_eq!(plugin_id, "test");
+            statuses.push(status);
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                crate::model::PluginStatus::Error(String::from("no index")),
+                crate::model::PluginStatus::Loading,
+            ]
+        );
+    }
+
+    #[test]
+    fn catches_panics_and_errors() {
+        let mut supervisor = Supervisor::new("test");
+        let error = iced::futures::executor::block_on(supervisor.run(async {
+            panic!("broken index");
+        }));
+        assert_eq!(error.to_string(), "Plugin panicked: broken index");
+
+        let error = iced::futures::executor::block_on(
+            supervisor.run(async { Err(anyhow::anyhow!("no index")) }),
+        );
+        assert_eq!(error.to_string(), "no index");
+
+        let error = iced::futures::executor::block_on(supervisor.run(async { Ok(()) }));
+        assert_eq!(error.to_string(), "Plugin stopped unexpectedly.");
+    }
+}
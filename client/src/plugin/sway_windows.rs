@@ -2,11 +2,43 @@ use crate::plugin::utils::Plugin;
 use anyhow::Context;
 
 pub struct SwayWindowsPlugin {
-    sway: swayipc::Connection,
+    sway: Option<swayipc::Connection>,
     entries: Vec<crate::model::Entry>,
+    icons: crate::plugin::icons::IconLookup,
 }
 
 impl SwayWindowsPlugin {
+    pub fn new() -> Self {
+        Self {
+            sway: None,
+            entries: vec![],
+            icons: crate::plugin::icons::IconLookup::new(),
+        }
+    }
+
+    /// Sends a request over the sway ipc connection, which is established on
+    /// first use and kept afterwards.
+    async fn request<T: std::marker::Send + 'static>(
+        &mut self,
+        request: impl FnOnce(&mut swayipc::Connection) -> anyhow::Result<T>
+            + std::marker::Send
+            + 'static,
+    ) -> anyhow::Result<T> {
+        let sway = self.sway.take();
+        let (sway, result) = crate::plugin::utils::spawn_blocking(move || {
+            let mut sway = match sway {
+                Some(sway) => sway,
+                None => swayipc::Connection::new()
+                    .context("Failed to establish sway ipc connection.")?,
+            };
+            let result = request(&mut sway);
+            Ok((sway, result))
+        })
+        .await?;
+        self.sway = Some(sway);
+        result
+    }
+
     fn get_window_nodes(node: swayipc::Node) -> Vec<swayipc::Node> {
         if !node.nodes.is_empty() {
             return node
@@ -24,14 +56,15 @@ impl SwayWindowsPlugin {
     }
 }
 
+#[async_trait::async_trait]
 impl Plugin for SwayWindowsPlugin {
-    fn id() -> &'static str {
-        "sway-windows"
+    fn id(&self) -> &str {
+        "sway_windows"
     }
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         30
     }
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰖯 Windows"
     }
 
@@ -39,56 +72,102 @@ impl Plugin for SwayWindowsPlugin {
         self.entries.clone()
     }
 
-    fn new() -> Self {
-        let connection_result =
-            swayipc::Connection::new().context("Failed to establish sway ipc connection.");
-        if let Err(error) = connection_result {
-            log::error!(target: Self::id(), "{:?}", error);
-            panic!();
-        }
-        let mut sway = connection_result.unwrap();
-
-        let root_node_result = sway.get_tree().context("Failed to get_tree from sway ipc.");
-        if let Err(error) = root_node_result {
-            log::error!(target: Self::id(), "{:?}", error);
-            panic!();
-        }
-        let root_node = root_node_result.unwrap();
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
+        let root_node = self
+            .request(|sway| sway.get_tree().context("Failed to get_tree from sway ipc."))
+            .await?;
 
-        let entries = Self::get_window_nodes(root_node)
-            .into_iter()
-            .map(|node| {
-                let name = node
-                    .name
-                    .unwrap_or(String::from("-- window name missing --"));
-                let app_id = node
-                    .app_id
-                    .unwrap_or(String::from("-- window app_id missing --"));
-                let title = if !name.is_empty() { name } else { app_id };
-                crate::model::Entry {
-                    id: node.id.to_string(),
-                    title,
-                    action: String::from("focus"),
-                    meta: String::from("Sway Windows"),
-                    command: None,
-                }
-            })
-            .collect();
+        // Resolving icons may walk the icon themes, so it happens off the
+        // async executor with the lookup handed back afterwards.
+        let mut icons = std::mem::take(&mut self.icons);
+        let (icons, entries) = crate::plugin::utils::spawn_blocking(move || {
+            let entries = Self::get_window_nodes(root_node)
+                .into_iter()
+                .map(|node| {
+                    // Xwayland windows have no app_id, but their class usually
+                    // names the icon just as well.
+                    let icon_name = node.app_id.clone().or_else(|| {
+                        node.window_properties
+                            .as_ref()
+                            .and_then(|properties| properties.class.clone())
+                    });
+                    let icon = icon_name.and_then(|icon_name| {
+                        icons
+                            .find(&icon_name)
+                            .or_else(|| icons.find(&icon_name.to_lowercase()))
+                    });
+                    let name = node
+                        .name
+                        .unwrap_or(String::from("-- window name missing --"));
+                    let app_id = node
+                        .app_id
+                        .unwrap_or(String::from("-- window app_id missing --"));
+                    let title = if !name.is_empty() { name } else { app_id };
+                    crate::model::Entry {
+                        id: node.id.to_string(),
+                        title,
+                        actions: vec![
+                            crate::model::Action::new("focus", "focus"),
+                            crate::model::Action::new(
+                                "move-to-current-workspace",
+                                "move to current workspace",
+                            ),
+                            crate::model::Action::new("kill", "kill"),
+                        ],
+                        meta: String::from("Sway Windows"),
+                        command: None,
+                        kind: None,
+                        icon,
+                        highlights: vec![],
+                        score: 0,
+                        matched_with_typos: false,
+                    }
+                })
+                .collect();
+            Ok((icons, entries))
+        })
+        .await?;
+        self.icons = icons;
+        self.entries = entries;
 
-        Self { sway, entries }
+        Ok(())
     }
 
-    fn activate(
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
-        self.sway
-            .run_command(format!("[con_id={}] focus", entry.id))
-            .context(format!(
-                "Failed to focus window while activating entry with id '{}'.",
-                entry.id
-            ))?;
+        if !["focus", "move-to-current-workspace", "kill"].contains(&action) {
+            return Err(crate::plugin::utils::unknown_action(&entry, action));
+        }
+        let window = format!("[con_id={}]", entry.id);
+        let action = String::from(action);
+        self.request(move |sway| {
+            let command = match action.as_str() {
+                "kill" => format!("{window} kill"),
+                "move-to-current-workspace" => {
+                    let workspace = sway
+                        .get_workspaces()
+                        .context("Failed to get_workspaces from sway ipc.")?
+                        .into_iter()
+                        .find(|workspace| workspace.focused)
+                        .context("Failed to find the focused sway workspace.")?;
+                    format!(
+                        "{window} move container to workspace \"{}\"; {window} focus",
+                        workspace.name
+                    )
+                }
+                _ => format!("{window} focus"),
+            };
+            sway.run_command(command).map_err(Into::into)
+        })
+        .await
+        .context(format!(
+            "Failed to run sway command while activating entry with id '{}'.",
+            entry.id
+        ))?;
 
         plugin_channel_out
             .try_send(crate::Message::Exit)
@@ -6,54 +6,77 @@ pub struct SystemPlugin {
     entries: Vec<crate::model::Entry>,
 }
 
-impl Plugin for SystemPlugin {
-    fn new() -> Self {
+impl SystemPlugin {
+    pub fn new() -> Self {
         Self { entries: vec![] }
     }
+}
 
-    fn id() -> &'static str {
+#[async_trait::async_trait]
+impl Plugin for SystemPlugin {
+    fn id(&self) -> &str {
         "system"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         15
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰌢 System"
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
 
         self.entries = vec![
             crate::model::Entry {
                 id: String::from("lock"),
                 title: String::from("Lock"),
-                action: String::from(""),
+                actions: vec![],
                 meta: String::from("System"),
                 command: Some(vec![String::from("lock")]),
+                kind: None,
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             },
             crate::model::Entry {
                 id: String::from("restart"),
                 title: String::from("Restart"),
-                action: String::from(""),
+                actions: vec![],
                 meta: String::from("System"),
                 command: Some(vec![String::from("reboot")]),
+                kind: None,
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             },
             crate::model::Entry {
                 id: String::from("shutdown"),
                 title: String::from("Shutdown"),
-                action: String::from(""),
+                actions: vec![],
                 meta: String::from("System"),
                 command: Some(vec![String::from("poweroff")]),
+                kind: None,
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             },
             crate::model::Entry {
                 id: String::from("sleep"),
                 title: String::from("Sleep"),
-                action: String::from(""),
+                actions: vec![],
                 meta: String::from("System Hibernate Suspend"),
                 command: Some(vec![String::from("systemctl suspend")]),
+                kind: None,
+                icon: None,
+                highlights: vec![],
+                score: 0,
+                matched_with_typos: false,
             },
         ];
 
@@ -64,9 +87,10 @@ impl Plugin for SystemPlugin {
         self.entries.clone()
     }
 
-    fn activate(
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        _action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
         let command = entry.command.context(format!(
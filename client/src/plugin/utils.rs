@@ -2,76 +2,124 @@ use anyhow::Context;
 use iced::futures::StreamExt;
 
 pub fn spawn<PluginType: Plugin + std::marker::Send + 'static>(
+    new: fn() -> PluginType,
 ) -> iced::Subscription<crate::Message> {
-    iced::subscription::channel(
-        std::any::TypeId::of::<PluginType>(),
-        100,
-        |plugin_channel_out| async move {
-            let mut plugin = PluginType::new();
-
-            let main_loop_result = plugin.main(plugin_channel_out).await;
-            if let Err(error) = main_loop_result {
-                log::error!(
-                    target: PluginType::id(),
-                    "{:?}", error,
-                );
-                panic!();
-            }
+    spawn_with_id(std::any::TypeId::of::<PluginType>(), new)
+}
 
-            #[allow(clippy::never_loop)]
-            loop {
-                unreachable!();
-            }
-        },
-    )
+/// Spawns a plugin of which several instances may run, each with its own subscription id.
+///
+/// The plugin is registered once and supervised: when it fails, a new
+/// instance is created with `new` and takes over after a backoff.
+pub fn spawn_with_id<PluginType: Plugin + std::marker::Send + 'static>(
+    id: impl std::hash::Hash + 'static,
+    new: impl Fn() -> PluginType + std::marker::Send + 'static,
+) -> iced::Subscription<crate::Message> {
+    iced::subscription::channel(id, 100, |mut plugin_channel_out| async move {
+        let mut plugin = new();
+        let mut supervisor = crate::plugin::supervisor::Supervisor::new(plugin.id());
+
+        let (mut app_channel_out, mut plugin_channel_in) =
+            iced::futures::channel::mpsc::channel(100);
+        if let Err(error) = plugin.register_plugin(&mut plugin_channel_out, &mut app_channel_out) {
+            log::error!(target: plugin.id(), "{:?}", error);
+        }
+        // The query and the queued requests outlive restarts, so a restarted
+        // plugin continues where the failed one stopped.
+        let mut last_query = crate::search::query::Query::default();
+        let mut plugin_requests = std::collections::VecDeque::new();
+
+        loop {
+            let error = supervisor
+                .run(plugin.main(
+                    &mut plugin_channel_out,
+                    &mut plugin_channel_in,
+                    &mut last_query,
+                    &mut plugin_requests,
+                ))
+                .await;
+            supervisor.recover(error, &mut plugin_channel_out).await;
+            plugin = new();
+        }
+    })
 }
 
 #[async_trait::async_trait]
 pub trait Plugin {
-    fn id() -> &'static str;
-    fn priority() -> u32;
-    fn title() -> &'static str;
-    fn update_timeout() -> Option<std::time::Duration> {
+    fn id(&self) -> &str;
+    fn priority(&self) -> u32;
+    fn title(&self) -> &str;
+    fn update_timeout(&self) -> Option<std::time::Duration> {
         None
     }
-
-    fn new() -> Self;
+    /// Whether the plugin implements [`Self::preview`]. Only such plugins are
+    /// asked for previews.
+    fn has_preview(&self) -> bool {
+        false
+    }
 
     fn entries(&self) -> Vec<crate::model::Entry>;
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    /// Loads the entries. Blocking work belongs in [`spawn_blocking`].
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         Ok(())
     }
 
+    /// Loads the entries on start in batches, so large sources show up while
+    /// they are still loading. Each loaded batch is added to the entries and
+    /// passed to [`EntryBatches::loaded`]. Defaults to `update_entries`, which
+    /// is still used for updates on timeout.
+    async fn stream_entries(&mut self, _batches: &mut EntryBatches<'_>) -> anyhow::Result<()> {
+        self.update_entries().await
+    }
+
     fn plugin(
         &self,
         app_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::model::PluginRequest>,
     ) -> crate::model::Plugin {
         crate::model::Plugin {
-            id: String::from(Self::id()),
-            priority: Self::priority(),
-            title: String::from(Self::title()),
+            id: String::from(self.id()),
+            priority: self.priority(),
+            title: String::from(self.title()),
             app_channel_out: app_channel_out.clone(),
             entries: self.entries(),
+            status: crate::model::PluginStatus::default(),
+            search_pending: false,
+            has_preview: self.has_preview(),
         }
     }
 
+    /// Loads the entries and answers requests of the app until an error occurs.
     async fn main(
         &mut self,
-        mut plugin_channel_out: iced::futures::channel::mpsc::Sender<crate::Message>,
+        plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
+        plugin_channel_in: &mut iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+        last_query: &mut crate::search::query::Query,
+        plugin_requests: &mut std::collections::VecDeque<crate::model::PluginRequest>,
     ) -> anyhow::Result<()> {
-        self.update_entries()?;
+        let mut batches = EntryBatches {
+            plugin_channel_out,
+            plugin_channel_in,
+            last_query,
+            plugin_requests,
+        };
+        self.stream_entries(&mut batches).await?;
 
-        let (mut app_channel_out, mut plugin_channel_in) =
-            iced::futures::channel::mpsc::channel(100);
-        self.register_plugin(&mut plugin_channel_out, &mut app_channel_out)?;
-        let mut last_query = String::from("");
+        plugin_channel_out
+            .try_send(crate::Message::UpdatePluginStatus(
+                String::from(self.id()),
+                crate::model::PluginStatus::Ready,
+            ))
+            .context("Failed to send message to update plugin status.")?;
+        let mut shown_entries = self.search(last_query, plugin_channel_out).await?;
 
         loop {
             self.update(
-                &mut plugin_channel_out,
-                &mut plugin_channel_in,
-                &mut last_query,
+                plugin_channel_out,
+                plugin_channel_in,
+                plugin_requests,
+                last_query,
+                &mut shown_entries,
             )
             .await?;
         }
@@ -93,78 +141,483 @@ pub trait Plugin {
         &mut self,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
         plugin_channel_in: &mut iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
-        last_query: &mut String,
+        plugin_requests: &mut std::collections::VecDeque<crate::model::PluginRequest>,
+        last_query: &mut crate::search::query::Query,
+        shown_entries: &mut Vec<crate::model::Entry>,
     ) -> anyhow::Result<()> {
-        let plugin_request_future = plugin_channel_in.select_next_some();
-        let plugin_request = match Self::update_timeout() {
-            Some(update_timeout) => {
-                async_std::future::timeout(update_timeout, plugin_request_future)
-                    .await
-                    .unwrap_or(crate::model::PluginRequest::Timeout)
+        if plugin_requests.is_empty() {
+            let plugin_request_future = plugin_channel_in.select_next_some();
+            let plugin_request = match self.update_timeout() {
+                Some(update_timeout) => {
+                    async_std::future::timeout(update_timeout, plugin_request_future)
+                        .await
+                        .unwrap_or(crate::model::PluginRequest::Timeout)
+                }
+                None => plugin_request_future.await,
+            };
+            plugin_requests.push_back(plugin_request);
+        }
+
+        // Requests queued up while the plugin was busy are taken at once, so
+        // only the latest of several searches or previews is run.
+        while let Ok(Some(plugin_request)) = plugin_channel_in.try_next() {
+            plugin_requests.push_back(plugin_request);
+        }
+        merge_requests(plugin_requests);
+
+        match plugin_requests.pop_front() {
+            Some(plugin_request) => {
+                self.handle_request(
+                    plugin_request,
+                    plugin_channel_out,
+                    last_query,
+                    shown_entries,
+                )
+                .await
             }
-            None => plugin_request_future.await,
-        };
+            None => Ok(()),
+        }
+    }
 
+    async fn handle_request(
+        &mut self,
+        plugin_request: crate::model::PluginRequest,
+        plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
+        last_query: &mut crate::search::query::Query,
+        shown_entries: &mut Vec<crate::model::Entry>,
+    ) -> anyhow::Result<()> {
         match plugin_request {
             crate::model::PluginRequest::Search(query) => {
-                self.search(&query, plugin_channel_out)?;
+                *shown_entries = self.search(&query, plugin_channel_out).await?;
                 *last_query = query;
             }
             crate::model::PluginRequest::Timeout => {
-                self.update_entries()?;
-                self.search(last_query, plugin_channel_out)?;
+                self.update_entries().await?;
+                self.refresh(last_query, shown_entries, plugin_channel_out)
+                    .await?;
+            }
+            crate::model::PluginRequest::Activate(entry, action) => {
+                // A failing activation, like an unknown action, is no reason
+                // to restart the plugin either.
+                if let Err(error) = self.activate(entry, &action, plugin_channel_out).await {
+                    log::warn!(target: self.id(), "{:?}", error);
+                }
             }
-            crate::model::PluginRequest::Activate(entry) => {
-                self.activate(entry, plugin_channel_out)?
+            crate::model::PluginRequest::Preview(entry) => {
+                // A failing preview is no reason to restart the plugin.
+                match self.preview(&entry).await {
+                    Ok(Some(preview)) => plugin_channel_out
+                        .try_send(crate::Message::UpdatePreview(
+                            String::from(self.id()),
+                            entry.id.clone(),
+                            preview,
+                        ))
+                        .context(format!(
+                            "Failed to send message to update preview of entry with id '{}'.",
+                            entry.id
+                        ))?,
+                    Ok(None) => {}
+                    Err(error) => log::warn!(target: self.id(), "{:?}", error),
+                }
             }
         }
 
         return Ok(());
     }
 
-    fn search(
+    /// Narrows the entries to search in. Plugins supporting entry filters of
+    /// the query, like `after:`, apply them here and remove them from the query.
+    fn apply_filters(
+        &self,
+        entries: Vec<crate::model::Entry>,
+        _query: &mut crate::search::query::Query,
+    ) -> Vec<crate::model::Entry> {
+        entries
+    }
+
+    /// The entries matching the query, best first.
+    async fn results(
         &mut self,
-        query: &str,
+        query: &crate::search::query::Query,
+    ) -> anyhow::Result<Vec<crate::model::Entry>> {
+        let mut query = query.clone();
+        let entries = self.apply_filters(self.entries(), &mut query);
+        spawn_search(self.id(), entries, query).await
+    }
+
+    /// Sends the results for the query to the app. Returns the shown entries
+    /// for [`Self::refresh`], which only plugins updating on timeout need.
+    async fn search(
+        &mut self,
+        query: &crate::search::query::Query,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
-    ) -> anyhow::Result<()> {
-        let filtered_entries = crate::plugin::utils::search(self.entries(), query);
+    ) -> anyhow::Result<Vec<crate::model::Entry>> {
+        let mut results = self.results(query).await?;
+        retain_kind(&mut results, query);
+        let shown_entries = match self.update_timeout() {
+            Some(_) => results.clone(),
+            None => vec![],
+        };
 
         plugin_channel_out
             .try_send(crate::Message::UpdateEntries(
-                String::from(Self::id()),
-                filtered_entries,
+                String::from(self.id()),
+                query.generation,
+                results,
             ))
             .context(format!(
                 "Failed to send message to update entries while searching for '{}'.",
-                query
+                query.text
             ))?;
 
+        Ok(shown_entries)
+    }
+
+    /// Sends only the changes to the shown entries after the entries were
+    /// updated, so the app can patch its entries in place.
+    async fn refresh(
+        &mut self,
+        query: &crate::search::query::Query,
+        shown_entries: &mut Vec<crate::model::Entry>,
+        plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
+    ) -> anyhow::Result<()> {
+        let mut results = self.results(query).await?;
+        retain_kind(&mut results, query);
+
+        let message = match crate::model::EntriesPatch::between(shown_entries, &results) {
+            Some(patch) if patch.is_empty() => None,
+            Some(patch) => Some(crate::Message::PatchEntries(
+                String::from(self.id()),
+                query.generation,
+                patch,
+            )),
+            None => Some(crate::Message::UpdateEntries(
+                String::from(self.id()),
+                query.generation,
+                results.clone(),
+            )),
+        };
+        if let Some(message) = message {
+            plugin_channel_out.try_send(message).context(format!(
+                "Failed to send message to update entries while refreshing '{}'.",
+                query.text
+            ))?;
+        }
+        *shown_entries = results;
+
         Ok(())
     }
 
-    fn activate(
+    /// Details about the entry for the preview pane. Only asked for if
+    /// [`Self::has_preview`] is true.
+    async fn preview(
+        &mut self,
+        _entry: &crate::model::Entry,
+    ) -> anyhow::Result<Option<crate::model::Preview>> {
+        Ok(None)
+    }
+
+    /// Runs the action with the id, one of the entry's actions, on the entry.
+    async fn activate(
         &mut self,
         _entry: crate::model::Entry,
+        _action: &str,
         _plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
         Ok(())
     }
 }
 
-pub fn search(entries: Vec<crate::model::Entry>, query: &str) -> Vec<crate::model::Entry> {
+/// Hands the entry batches of a plugin that is still loading to the app.
+pub struct EntryBatches<'a> {
+    plugin_channel_out: &'a mut iced::futures::channel::mpsc::Sender<crate::Message>,
+    plugin_channel_in: &'a mut iced::futures::channel::mpsc::Receiver<crate::model::PluginRequest>,
+    last_query: &'a mut crate::search::query::Query,
+    // Requests other than searches wait until loading is done.
+    plugin_requests: &'a mut std::collections::VecDeque<crate::model::PluginRequest>,
+}
+
+impl EntryBatches<'_> {
+    /// Appends the matches of a batch, which the plugin has already added to
+    /// its entries, to the results shown in the app. When the query changed in
+    /// the meantime, all entries loaded so far are searched again instead.
+    pub async fn loaded<PluginType: Plugin + std::marker::Send + ?Sized>(
+        &mut self,
+        plugin: &mut PluginType,
+        batch: Vec<crate::model::Entry>,
+    ) -> anyhow::Result<()> {
+        let mut query_changed = false;
+        while let Ok(Some(plugin_request)) = self.plugin_channel_in.try_next() {
+            match plugin_request {
+                crate::model::PluginRequest::Search(query) => {
+                    *self.last_query = query;
+                    query_changed = true;
+                }
+                crate::model::PluginRequest::Timeout => {}
+                plugin_request => self.plugin_requests.push_back(plugin_request),
+            }
+        }
+        if query_changed {
+            plugin
+                .search(self.last_query, self.plugin_channel_out)
+                .await?;
+            return Ok(());
+        }
+
+        // Typos are only tolerated once all entries are loaded, as the
+        // fallback depends on the matches among all of them.
+        let mut query = self.last_query.clone();
+        let mut batch = plugin.apply_filters(batch, &mut query);
+        retain_kind(&mut batch, &query);
+        let plugin_id = String::from(plugin.id());
+        let batch_query = query.clone();
+        let matched_entries =
+            spawn_blocking(move || Ok(search_entries(&plugin_id, batch, &batch_query, false)))
+                .await?;
+        self.plugin_channel_out
+            .try_send(crate::Message::AppendEntries(
+                String::from(plugin.id()),
+                query.generation,
+                matched_entries,
+            ))
+            .context(format!(
+                "Failed to send message to append entries while searching for '{}'.",
+                query.text
+            ))?;
+
+        Ok(())
+    }
+}
+
+/// Drops all but the latest of the queued searches and all but the latest of
+/// the queued previews. The other requests keep their order.
+pub fn merge_requests(
+    plugin_requests: &mut std::collections::VecDeque<crate::model::PluginRequest>,
+) {
+    let latest_search_index = plugin_requests.iter().rposition(|plugin_request| {
+        matches!(plugin_request, crate::model::PluginRequest::Search(_))
+    });
+    let latest_preview_index = plugin_requests.iter().rposition(|plugin_request| {
+        matches!(plugin_request, crate::model::PluginRequest::Preview(_))
+    });
+
+    let mut index = 0;
+    plugin_requests.retain(|plugin_request| {
+        let keep = match plugin_request {
+            crate::model::PluginRequest::Search(_) => Some(index) == latest_search_index,
+            crate::model::PluginRequest::Preview(_) => Some(index) == latest_preview_index,
+            _ => true,
+        };
+        index += 1;
+        keep
+    });
+}
+
+/// Runs blocking work, like reading files, SQLite or D-Bus, on the blocking
+/// thread pool, so a slow plugin does not stall the executor shared by all
+/// plugins. Panics are returned as errors.
+pub async fn spawn_blocking<T: std::marker::Send + 'static>(
+    work: impl FnOnce() -> anyhow::Result<T> + std::marker::Send + 'static,
+) -> anyhow::Result<T> {
+    async_std::task::spawn_blocking(move || {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(work)).unwrap_or_else(|panic| {
+            Err(anyhow::anyhow!(
+                "Plugin panicked: {}",
+                crate::plugin::supervisor::panic_message(&*panic)
+            ))
+        })
+    })
+    .await
+}
+
+/// Runs [`search`] on the blocking thread pool, since matching thousands of
+/// entries on every keystroke would stall the executor shared by all plugins.
+pub async fn spawn_search(
+    plugin_id: &str,
+    entries: Vec<crate::model::Entry>,
+    query: crate::search::query::Query,
+) -> anyhow::Result<Vec<crate::model::Entry>> {
+    let plugin_id = String::from(plugin_id);
+    spawn_blocking(move || Ok(search(&plugin_id, entries, &query))).await
+}
+
+/// Drops the entries not of the kind asked for with `kind:`. Applied to the
+/// results of every plugin, including those producing their own results.
+pub fn retain_kind(entries: &mut Vec<crate::model::Entry>, query: &crate::search::query::Query) {
+    if let Some(kind_filter) = &query.filters.kind {
+        entries.retain(|entry| {
+            entry
+                .kind
+                .as_ref()
+                .is_some_and(|kind| kind.name() == kind_filter)
+        });
+    }
+}
+
+// Entries are matched with typos only if fewer entries match without.
+const TYPO_FALLBACK_MAX_HITS: usize = 3;
+
+// Matching with typos is far slower than fuzzy matching, so only this many
+// entries, in the order of the plugin, are tried.
+const TYPO_FALLBACK_MAX_CANDIDATES: usize = 10_000;
+
+/// The entries matching the query, best first. Entries are matched with
+/// typos if hardly any entries match without.
+pub fn search(
+    plugin_id: &str,
+    entries: Vec<crate::model::Entry>,
+    query: &crate::search::query::Query,
+) -> Vec<crate::model::Entry> {
+    search_entries(plugin_id, entries, query, true)
+}
+
+fn search_entries(
+    plugin_id: &str,
+    entries: Vec<crate::model::Entry>,
+    query: &crate::search::query::Query,
+    typo_fallback: bool,
+) -> Vec<crate::model::Entry> {
+    // Entries of plugins that do not support the entry filters never match them.
+    if query.filters.has_entry_filters() {
+        return vec![];
+    }
+
+    let folding = crate::search::fuzzy::Folding::shared();
+    let frecency = crate::search::frecency::Frecency::shared();
+    let associations = crate::search::associations::Associations::shared();
+    let association_boosts = associations.entry_boosts(&query.text, plugin_id);
+
     if query.is_empty() {
-        let mut sorted_entries = entries.clone();
-        sorted_entries.sort_by_key(|entry| entry.title.clone());
+        let mut sorted_entries: Vec<crate::model::Entry> = entries
+            .into_iter()
+            .map(|mut entry| {
+                entry.score = frecency.entry_boost(plugin_id, &entry.id);
+                entry
+            })
+            .collect();
+        sorted_entries.sort_by(compare_results);
         return sorted_entries;
     }
 
+    let score_entry = |entry: &mut crate::model::Entry,
+                       entry_match: crate::search::fuzzy::Match| {
+        entry.highlights = crate::search::fuzzy::to_ranges(&entry_match.positions);
+        entry.score = entry_match.score
+            + frecency.entry_boost(plugin_id, &entry.id)
+            + association_boosts
+                .get(entry.id.as_str())
+                .copied()
+                .unwrap_or(0);
+    };
+
+    let mut scored_entries: Vec<crate::model::Entry> = vec![];
+    let mut unmatched_entries: Vec<crate::model::Entry> = vec![];
+    for mut entry in entries {
+        match crate::search::fuzzy::match_entry(&entry, query, folding) {
+            Some(entry_match) => {
+                score_entry(&mut entry, entry_match);
+                scored_entries.push(entry);
+            }
+            None => unmatched_entries.push(entry),
+        }
+    }
+
+    let needs_typo_fallback = typo_fallback
+        && scored_entries.len() < TYPO_FALLBACK_MAX_HITS
+        && query
+            .terms
+            .iter()
+            .any(|term| crate::search::fuzzy::allows_typos(term));
+    if needs_typo_fallback {
+        let mut typo_matcher = crate::search::fuzzy::TypoMatcher::new(folding);
+        scored_entries.extend(
+            unmatched_entries
+                .into_iter()
+                .take(TYPO_FALLBACK_MAX_CANDIDATES)
+                .filter_map(|mut entry| {
+                    let entry_match =
+                        crate::search::fuzzy::typo_match_entry(&entry, query, &mut typo_matcher)?;
+                    score_entry(&mut entry, entry_match);
+                    entry.matched_with_typos = true;
+                    Some(entry)
+                }),
+        );
+    }
+
+    scored_entries.sort_by(compare_results);
+
+    // The learned pick for the query leads the results, no matter its score.
+    let learned_pick = associations
+        .pick(&query.text)
+        .filter(|(pick_plugin_id, _)| *pick_plugin_id == plugin_id);
+    if let Some((_, entry_id)) = learned_pick {
+        if let Some(index) = scored_entries.iter().position(|entry| entry.id == entry_id) {
+            scored_entries[..=index].rotate_right(1);
+        }
+    }
+
+    scored_entries
+}
+
+/// Orders results best first: entries matching without typos, then by score,
+/// then shorter and alphabetically earlier titles.
+pub fn compare_results(
+    a_entry: &crate::model::Entry,
+    b_entry: &crate::model::Entry,
+) -> std::cmp::Ordering {
+    a_entry
+        .matched_with_typos
+        .cmp(&b_entry.matched_with_typos)
+        .then(b_entry.score.cmp(&a_entry.score))
+        .then(
+            a_entry
+                .title
+                .chars()
+                .count()
+                .cmp(&b_entry.title.chars().count()),
+        )
+        .then(a_entry.title.cmp(&b_entry.title))
+}
+
+/// Keeps the entries whose id is an url on the host of the `host:` filter and
+/// removes the filter from the query.
+pub fn apply_host_filter(
+    entries: Vec<crate::model::Entry>,
+    query: &mut crate::search::query::Query,
+) -> Vec<crate::model::Entry> {
+    let Some(host_filter) = query.filters.host.take() else {
+        return entries;
+    };
+
     entries
         .into_iter()
-        .filter(|entry| {
-            let keywords = format!("{} {}", entry.title, entry.meta).to_lowercase();
-            keywords.contains(&query.to_lowercase())
-        })
-        .collect::<Vec<crate::model::Entry>>()
+        .filter(|entry| crate::search::query::matches_host(&entry.id, &host_filter))
+        .collect()
+}
+
+/// Copies the text to the wayland clipboard. `wl-copy` keeps serving the
+/// clipboard after centerpiece exits.
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    std::process::Command::new("wl-copy")
+        .arg("--")
+        .arg(text)
+        .spawn()
+        .context("Failed to run wl-copy, is wl-clipboard installed?")?;
+    Ok(())
+}
+
+/// The error for an action id the plugin does not offer for the entry.
+pub fn unknown_action(entry: &crate::model::Entry, action: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Unknown action '{}' for entry with id '{}'.",
+        action,
+        entry.id
+    )
+}
+
+/// The terminal emulator set in `$TERMINAL`, alacritty by default.
+pub fn terminal() -> String {
+    std::env::var("TERMINAL").unwrap_or(String::from("alacritty"))
 }
 
 pub fn config_directory() -> anyhow::Result<String> {
@@ -205,13 +658,306 @@ where
         std::fs::File::open(index_file_path).context("Error while opening index file")?;
 
     let reader = std::io::BufReader::new(index_file);
-    let git_repository_paths_result: Result<T, _> = serde_json::from_reader(reader);
-    if let Err(error) = git_repository_paths_result {
-        log::error!(
-            error = log::as_error!(error);
-            "Error while reading index file",
+    serde_json::from_reader(reader).context("Error while reading index file")
+}
+
+/// A directory for files written by tests, removed with its content on drop.
+#[cfg(test)]
+pub struct TestDirectory(std::path::PathBuf);
+
+#[cfg(test)]
+impl TestDirectory {
+    /// Creates the directory, the name has to be unique among all tests.
+    pub fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("centerpiece-tests-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDirectory {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_requests;
+    use crate::model::PluginRequest;
+
+    fn search(generation: u64) -> PluginRequest {
+        PluginRequest::Search(crate::search::query::Query {
+            generation,
+            ..Default::default()
+        })
+    }
+
+    fn entry(id: &str) -> crate::model::Entry {
+        crate::model::Entry {
+            id: String::from(id),
+            title: String::from(id),
+            actions: vec![crate::model::Action::new("open", "open")],
+            meta: String::new(),
+            command: None,
+            kind: None,
+            icon: None,
+            highlights: vec![],
+            score: 0,
+            matched_with_typos: false,
+        }
+    }
+
+    fn describe(plugin_requests: &std::collections::VecDeque<PluginRequest>) -> Vec<String> {
+        plugin_requests
+            .iter()
+            .map(|plugin_request| match plugin_request {
+                PluginRequest::Search(query) => format!("search {}", query.generation),
+                PluginRequest::Timeout => String::from("timeout"),
+                PluginRequest::Activate(entry, action) => {
+                    format!("{} {}", action, entry.id)
+                }
+                PluginRequest::Preview(entry) => format!("preview {}", entry.id),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_only_the_latest_search() {
+        let activate = PluginRequest::Activate(entry("entry"), String::from("open"));
+        let mut plugin_requests = std::collections::VecDeque::from([
+            search(1),
+            activate,
+            search(2),
+            PluginRequest::Timeout,
+            search(3),
+        ]);
+
+        merge_requests(&mut plugin_requests);
+
+        assert_eq!(
+            describe(&plugin_requests),
+            vec!["open entry", "timeout", "search 3"]
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_latest_preview() {
+        let mut plugin_requests = std::collections::VecDeque::from([
+            PluginRequest::Preview(entry("first")),
+            search(1),
+            PluginRequest::Preview(entry("second")),
+            PluginRequest::Timeout,
+        ]);
+
+        merge_requests(&mut plugin_requests);
+
+        assert_eq!(
+            describe(&plugin_requests),
+            vec!["search 1", "preview second", "timeout"]
+        );
+    }
+
+    #[test]
+    fn leaves_requests_without_searches_alone() {
+        let mut plugin_requests = std::collections::VecDeque::from([PluginRequest::Timeout]);
+        merge_requests(&mut plugin_requests);
+        assert_eq!(describe(&plugin_requests), vec!["timeout"]);
+
+        let mut plugin_requests = std::collections::VecDeque::from([search(7)]);
+        merge_requests(&mut plugin_requests);
+        assert_eq!(describe(&plugin_requests), vec!["search 7"]);
+    }
+
+    fn titles(entries: &[crate::model::Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.title.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_typo_matches_below_all_other_matches() {
+        let entries = vec![entry("Firefox"), entry("Fierfox Notes")];
+        let query = crate::search::query::Query::parse("fierfox");
+
+        let results = super::search("test", entries, &query);
+
+        assert_eq!(titles(&results), vec!["Fierfox Notes", "Firefox"]);
+        assert!(!results[0].matched_with_typos);
+        assert!(results[1].matched_with_typos);
+    }
+
+    #[test]
+    fn matches_typos_in_the_first_entries_only() {
+        let mut entries: Vec<crate::model::Entry> = (0..super::TYPO_FALLBACK_MAX_CANDIDATES)
+            .map(|index| entry(&format!("Document {index}")))
+            .collect();
+        entries[5_000] = entry("Firefox");
+        entries.push(entry("Firefox Nightly"));
+        let query = crate::search::query::Query::parse("fierfox");
+
+        let results = super::search("test", entries, &query);
+
+        assert_eq!(titles(&results), vec!["Firefox"]);
+    }
+
+    struct BatchPlugin {
+        entries: Vec<crate::model::Entry>,
+        update_timeout: Option<std::time::Duration>,
+    }
+
+    #[async_trait::async_trait]
+    impl super::Plugin for BatchPlugin {
+        fn id(&self) -> &str {
+            "batch"
+        }
+
+        fn priority(&self) -> u32 {
+            0
+        }
+
+        fn title(&self) -> &str {
+            "Batch"
+        }
+
+        fn update_timeout(&self) -> Option<std::time::Duration> {
+            self.update_timeout
+        }
+
+        fn entries(&self) -> Vec<crate::model::Entry> {
+            self.entries.clone()
+        }
+    }
+
+    /// Loads the batches one after another while the requests are waiting,
+    /// returns the messages to the app and the requests left for later.
+    fn load_batches(
+        batches: Vec<Vec<crate::model::Entry>>,
+        query: &str,
+        requests: Vec<PluginRequest>,
+    ) -> (
+        Vec<crate::Message>,
+        std::collections::VecDeque<PluginRequest>,
+        u64,
+    ) {
+        let (mut plugin_channel_out, mut messages_in) = iced::futures::channel::mpsc::channel(100);
+        let (mut requests_out, mut plugin_channel_in) = iced::futures::channel::mpsc::channel(100);
+        for request in requests {
+            requests_out.try_send(request).unwrap();
+        }
+        let mut last_query = crate::search::query::Query::parse(query);
+        let mut plugin_requests = std::collections::VecDeque::new();
+        let mut entry_batches = super::EntryBatches {
+            plugin_channel_out: &mut plugin_channel_out,
+            plugin_channel_in: &mut plugin_channel_in,
+            last_query: &mut last_query,
+            plugin_requests: &mut plugin_requests,
+        };
+        let mut plugin = BatchPlugin {
+            entries: vec![],
+            update_timeout: None,
+        };
+
+        for batch in batches {
+            plugin.entries.extend(batch.iter().cloned());
+            iced::futures::executor::block_on(entry_batches.loaded(&mut plugin, batch)).unwrap();
+        }
+
+        let mut messages = vec![];
+        while let Ok(Some(message)) = messages_in.try_next() {
+            messages.push(message);
+        }
+        (messages, plugin_requests, last_query.generation)
+    }
+
+    #[test]
+    fn appends_the_matches_of_each_batch() {
+        let (messages, pending_requests, _) = load_batches(
+            vec![
+                vec![entry("Firefox"), entry("Files")],
+                vec![entry("Fire Alarm"), entry("Thunderbird")],
+            ],
+            "fire",
+            vec![],
+        );
+
+        let appended: Vec<Vec<String>> = messages
+            .into_iter()
+            .map(|message| match message {
+                crate::Message::AppendEntries(_, _, entries) => {
+                    entries.into_iter().map(|entry| entry.title).collect()
+                }
+                message => panic!("Expected appended entries, got {message:?}"),
+            })
+            .collect();
+        assert_eq!(appended, vec![vec!["Firefox"], vec!["Fire Alarm"]]);
+        assert!(pending_requests.is_empty());
+    }
+
+    #[test]
+    fn matches_batches_without_typos() {
+        let (messages, _, _) = load_batches(vec![vec![entry("Firefox")]], "fierfox", vec![]);
+
+        assert!(matches!(
+            &messages[..],
+            [crate::Message::AppendEntries(_, _, entries)] if entries.is_empty()
+        ));
+    }
+
+    #[test]
+    fn searches_all_loaded_entries_when_the_query_changes() {
+        let (messages, pending_requests, generation) = load_batches(
+            vec![
+                vec![entry("Firefox"), entry("Files")],
+                vec![entry("Fire Alarm")],
+            ],
+            "",
+            vec![
+                search(1),
+                PluginRequest::Timeout,
+                PluginRequest::Activate(entry("Files"), String::from("open")),
+                PluginRequest::Preview(entry("Firefox")),
+                search(2),
+            ],
+        );
+
+        assert_eq!(generation, 2);
+        assert!(matches!(
+            &messages[..],
+            [
+                crate::Message::UpdateEntries(_, 2, first_entries),
+                crate::Message::AppendEntries(_, 2, second_entries),
+            ] if first_entries.len() == 2 && second_entries.len() == 1
+        ));
+        assert_eq!(
+            describe(&pending_requests),
+            vec!["open Files", "preview Firefox"]
         );
-        panic!();
     }
-    Ok(git_repository_paths_result.unwrap())
+
+    #[test]
+    fn keeps_the_shown_entries_only_for_plugins_updating_on_timeout() {
+        use super::Plugin;
+
+        let (mut plugin_channel_out, _messages_in) = iced::futures::channel::mpsc::channel(100);
+        let query = crate::search::query::Query::parse("fire");
+        let mut plugin = BatchPlugin {
+            entries: vec![entry("Firefox"), entry("Thunderbird")],
+            update_timeout: None,
+        };
+        let shown_entries =
+            iced::futures::executor::block_on(plugin.search(&query, &mut plugin_channel_out))
+                .unwrap();
+        assert!(shown_entries.is_empty());
+
+        plugin.update_timeout = Some(std::time::Duration::from_secs(1));
+        let shown_entries =
+            iced::futures::executor::block_on(plugin.search(&query, &mut plugin_channel_out))
+                .unwrap();
+        assert_eq!(titles(&shown_entries), vec!["Firefox"]);
+    }
 }
@@ -11,7 +11,11 @@ pub struct WifiPlugin {
 }
 
 impl WifiPlugin {
-    fn get_access_point_entries(&self) -> Result<Vec<crate::model::Entry>> {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    fn get_access_point_entries() -> Result<Vec<crate::model::Entry>> {
         // get wifi device
         let dbus_connection = Connection::new_system()?;
         let nm = NetworkManager::new(&dbus_connection);
@@ -83,7 +87,7 @@ impl WifiPlugin {
                 Some(crate::model::Entry {
                     id: ssid.clone(),
                     title: format!("{}{} {}", strength_icon, connected_icon, ssid.clone()),
-                    action: String::from("connect"),
+                    actions: vec![crate::model::Action::new("connect", "connect")],
                     meta: String::from("wifi wlan wireless lan"),
                     command: Some(vec![
                         String::from("nmcli"),
@@ -92,6 +96,11 @@ impl WifiPlugin {
                         String::from("connect"),
                         ssid,
                     ]),
+                    kind: None,
+                    icon: None,
+                    highlights: vec![],
+                    score: 0,
+                    matched_with_typos: false,
                 })
             })
             .collect();
@@ -99,26 +108,24 @@ impl WifiPlugin {
     }
 }
 
+#[async_trait::async_trait]
 impl Plugin for WifiPlugin {
-    fn new() -> Self {
-        Self { entries: vec![] }
-    }
-
-    fn id() -> &'static str {
+    fn id(&self) -> &str {
         "wifi"
     }
 
-    fn priority() -> u32 {
+    fn priority(&self) -> u32 {
         18
     }
 
-    fn title() -> &'static str {
+    fn title(&self) -> &str {
         "󰖩 Wifi"
     }
 
-    fn update_entries(&mut self) -> anyhow::Result<()> {
+    async fn update_entries(&mut self) -> anyhow::Result<()> {
         self.entries.clear();
-        self.entries = self.get_access_point_entries()?;
+        // Scanning over D-Bus takes a while.
+        self.entries = crate::plugin::utils::spawn_blocking(Self::get_access_point_entries).await?;
         Ok(())
     }
 
@@ -126,11 +133,15 @@ impl Plugin for WifiPlugin {
         self.entries.clone()
     }
 
-    fn activate(
+    async fn activate(
         &mut self,
         entry: crate::model::Entry,
+        action: &str,
         plugin_channel_out: &mut iced::futures::channel::mpsc::Sender<crate::Message>,
     ) -> anyhow::Result<()> {
+        if action != "connect" {
+            return Err(crate::plugin::utils::unknown_action(&entry, action));
+        }
         let command = entry.command.context(format!(
             "Failed to unpack command while activating entry with id '{}'.",
             entry.id
@@ -0,0 +1,251 @@
+use anyhow::Context;
+
+const ASSOCIATIONS_FILE_NAME: &str = "associations.json";
+
+// Only the most recently used associations are kept to keep the store small.
+const MAX_ASSOCIATIONS: usize = 2000;
+
+const BOOST_WEIGHT: f64 = 64.;
+
+// An entry is only picked for a query after it was activated for it this many
+// times, and this many times more often than any other entry.
+const MIN_PICK_COUNT: u32 = 2;
+const MIN_PICK_MARGIN: u32 = 1;
+
+static SHARED: std::sync::OnceLock<Associations> = std::sync::OnceLock::new();
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Association {
+    pub query: String,
+    pub plugin_id: String,
+    pub entry_id: String,
+    pub count: u32,
+    pub timestamp: i64,
+}
+
+/// Persisted record of which entry was activated for which query.
+///
+/// Activating an entry associates it with every prefix of the typed query,
+/// so picking "Telegram" for "tele" also teaches "t", "te" and "tel".
+#[derive(Debug, Default)]
+pub struct Associations {
+    associations: Vec<Association>,
+}
+
+impl Associations {
+    /// Loads the store from the cache directory, falling back to an empty store.
+    pub fn load() -> Self {
+        let associations_result = read_associations();
+        if let Err(error) = &associations_result {
+            log::info!(
+                "No usable associations store found, starting empty: {:?}",
+                error
+            );
+        }
+
+        Self {
+            associations: associations_result.unwrap_or_default(),
+        }
+    }
+
+    /// Shares the state of the store at application start with all plugins,
+    /// keeping the associations of plugins with frecency enabled only. Later
+    /// calls are ignored.
+    pub fn share(&self, plugin_settings: &crate::settings::PluginSettings) {
+        let associations = Self {
+            associations: self
+                .associations
+                .iter()
+                .filter(|association| plugin_settings.frecency_enabled(&association.plugin_id))
+                .cloned()
+                .collect(),
+        };
+        if SHARED.set(associations).is_err() {
+            log::warn!("The associations store is shared already.");
+        }
+    }
+
+    /// The store shared with [`Self::share`], empty if it was never shared.
+    pub fn shared() -> &'static Self {
+        SHARED.get_or_init(Self::default)
+    }
+
+    pub fn record(&mut self, query: &str, plugin_id: &str, entry_id: &str) {
+        let query = normalize(query);
+        let timestamp = chrono::Utc::now().timestamp();
+
+        let prefixes = query
+            .char_indices()
+            .map(|(index, character)| &query[..index + character.len_utf8()]);
+        for prefix in prefixes {
+            let existing_association = self.associations.iter_mut().find(|association| {
+                association.query == prefix
+                    && association.plugin_id == plugin_id
+                    && association.entry_id == entry_id
+            });
+
+            match existing_association {
+                Some(association) => {
+                    association.count += 1;
+                    association.timestamp = timestamp;
+                }
+                None => self.associations.push(Association {
+                    query: String::from(prefix),
+                    plugin_id: String::from(plugin_id),
+                    entry_id: String::from(entry_id),
+                    count: 1,
+                    timestamp,
+                }),
+            }
+        }
+
+        if self.associations.len() > MAX_ASSOCIATIONS {
+            self.associations
+                .sort_by_key(|association| std::cmp::Reverse(association.timestamp));
+            self.associations.truncate(MAX_ASSOCIATIONS);
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
+        std::fs::create_dir_all(&cache_directory)
+            .context("Error while creating cache directory")?;
+
+        let associations_file =
+            std::fs::File::create(format!("{cache_directory}/{ASSOCIATIONS_FILE_NAME}"))
+                .context("Error while creating associations file")?;
+        serde_json::to_writer(
+            std::io::BufWriter::new(associations_file),
+            &self.associations,
+        )
+        .context("Error while writing associations file")?;
+
+        Ok(())
+    }
+
+    /// The entry most often activated for exactly this query as `(plugin_id, entry_id)`,
+    /// if it was activated often enough and clearly more often than any other entry.
+    pub fn pick(&self, query: &str) -> Option<(&str, &str)> {
+        let query = normalize(query);
+        let mut counts: Vec<&Association> = self
+            .associations
+            .iter()
+            .filter(|association| association.query == query)
+            .collect();
+        counts.sort_by_key(|association| std::cmp::Reverse(association.count));
+
+        let pick = counts.first()?;
+        let runner_up_count = counts.get(1).map_or(0, |association| association.count);
+        if pick.count < MIN_PICK_COUNT || pick.count < runner_up_count + MIN_PICK_MARGIN {
+            return None;
+        }
+        Some((pick.plugin_id.as_str(), pick.entry_id.as_str()))
+    }
+
+    /// Scores to add to the match scores of the plugin entries for the given query, keyed by entry id.
+    pub fn entry_boosts(
+        &self,
+        query: &str,
+        plugin_id: &str,
+    ) -> std::collections::HashMap<&str, i64> {
+        let query = normalize(query);
+        self.associations
+            .iter()
+            .filter(|association| association.query == query && association.plugin_id == plugin_id)
+            .map(|association| {
+                let boost = (f64::from(association.count).ln_1p() * BOOST_WEIGHT).round() as i64;
+                (association.entry_id.as_str(), boost)
+            })
+            .collect()
+    }
+}
+
+fn normalize(query: &str) -> String {
+    query.trim().to_lowercase()
+}
+
+fn read_associations() -> anyhow::Result<Vec<Association>> {
+    let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
+    let associations_file =
+        std::fs::File::open(format!("{cache_directory}/{ASSOCIATIONS_FILE_NAME}"))
+            .context("Error while opening associations file")?;
+
+    let reader = std::io::BufReader::new(associations_file);
+    serde_json::from_reader(reader).context("Error while reading associations file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Associations;
+
+    #[test]
+    fn learns_every_prefix_of_the_query() {
+        let mut associations = Associations::default();
+        associations.record("Tele", "applications", "telegram");
+        associations.record("tele", "applications", "telegram");
+
+        for query in ["t", "te", "tel", "tele", " TELE "] {
+            assert_eq!(
+                associations.pick(query),
+                Some(("applications", "telegram")),
+                "no pick for {query:?}"
+            );
+        }
+        assert_eq!(associations.pick("teleg"), None);
+        assert_eq!(associations.pick(""), None);
+    }
+
+    #[test]
+    fn picks_the_most_often_activated_entry() {
+        let mut associations = Associations::default();
+        associations.record("te", "applications", "terminal");
+        associations.record("te", "applications", "telegram");
+        associations.record("te", "applications", "telegram");
+        associations.record("te", "brave_history", "https://example.com/te");
+
+        assert_eq!(associations.pick("te"), Some(("applications", "telegram")));
+        // Shorter queries learned from longer ones count as well.
+        associations.record("ter", "applications", "terminal");
+        associations.record("term", "applications", "terminal");
+        assert_eq!(associations.pick("t"), Some(("applications", "terminal")));
+    }
+
+    #[test]
+    fn picks_nothing_after_a_single_activation() {
+        let mut associations = Associations::default();
+        associations.record("tele", "applications", "telegram");
+
+        assert_eq!(associations.pick("t"), None);
+        assert_eq!(associations.pick("tele"), None);
+        associations.record("tele", "applications", "telegram");
+        assert_eq!(associations.pick("t"), Some(("applications", "telegram")));
+    }
+
+    #[test]
+    fn picks_nothing_for_a_tie() {
+        let mut associations = Associations::default();
+        associations.record("te", "applications", "terminal");
+        associations.record("te", "applications", "terminal");
+        associations.record("te", "applications", "telegram");
+        associations.record("te", "applications", "telegram");
+
+        assert_eq!(associations.pick("te"), None);
+        associations.record("te", "applications", "telegram");
+        assert_eq!(associations.pick("te"), Some(("applications", "telegram")));
+    }
+
+    #[test]
+    fn boosts_the_entries_of_the_plugin_by_count() {
+        let mut associations = Associations::default();
+        associations.record("te", "applications", "terminal");
+        associations.record("te", "applications", "telegram");
+        associations.record("te", "applications", "telegram");
+        associations.record("te", "brave_history", "https://example.com/te");
+
+        let boosts = associations.entry_boosts("te", "applications");
+        assert_eq!(boosts.len(), 2);
+        assert!(boosts["telegram"] > boosts["terminal"]);
+        assert!(boosts["terminal"] > 0);
+        assert!(associations.entry_boosts("xyz", "applications").is_empty());
+    }
+}
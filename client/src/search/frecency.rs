@@ -0,0 +1,248 @@
+use anyhow::Context;
+
+const FRECENCY_FILE_NAME: &str = "frecency.json";
+
+// Only the most recent activations are kept to keep the store small.
+const MAX_ACTIVATIONS: usize = 1000;
+
+// The weight of an activation halves every week.
+const HALF_LIFE_IN_SECONDS: f64 = 7. * 24. * 60. * 60.;
+
+const ENTRY_BOOST_WEIGHT: f64 = 16.;
+const PLUGIN_BOOST_WEIGHT: f64 = 2.;
+
+static SHARED: std::sync::OnceLock<Frecency> = std::sync::OnceLock::new();
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Activation {
+    pub plugin_id: String,
+    pub entry_id: String,
+    pub timestamp: i64,
+}
+
+/// Persisted record of activated entries, used to rank entries by frequency and recency.
+#[derive(Debug, Default)]
+pub struct Frecency {
+    activations: Vec<Activation>,
+    scores: std::collections::HashMap<String, std::collections::HashMap<String, f64>>,
+}
+
+impl Frecency {
+    /// Loads the store from the cache directory, falling back to an empty store.
+    pub fn load() -> Self {
+        let activations_result = read_activations();
+        if let Err(error) = &activations_result {
+            log::info!(
+                "No usable frecency store found, starting empty: {:?}",
+                error
+            );
+        }
+
+        let mut frecency = Self {
+            activations: activations_result.unwrap_or_default(),
+            scores: Default::default(),
+        };
+        frecency.update_scores();
+        frecency
+    }
+
+    /// Shares the state of the store at application start with all plugins,
+    /// keeping the activations of plugins with frecency enabled only. Later
+    /// calls are ignored.
+    pub fn share(&self, plugin_settings: &crate::settings::PluginSettings) {
+        if SHARED.set(self.enabled_only(plugin_settings)).is_err() {
+            log::warn!("The frecency store is shared already.");
+        }
+    }
+
+    /// A copy of the store with the activations of plugins with frecency enabled only.
+    fn enabled_only(&self, plugin_settings: &crate::settings::PluginSettings) -> Self {
+        let mut frecency = Self {
+            activations: self
+                .activations
+                .iter()
+                .filter(|activation| plugin_settings.frecency_enabled(&activation.plugin_id))
+                .cloned()
+                .collect(),
+            scores: Default::default(),
+        };
+        frecency.update_scores();
+        frecency
+    }
+
+    /// The store shared with [`Self::share`], empty if it was never shared.
+    pub fn shared() -> &'static Self {
+        SHARED.get_or_init(Self::default)
+    }
+
+    pub fn record(&mut self, plugin_id: &str, entry_id: &str) {
+        self.record_at(plugin_id, entry_id, chrono::Utc::now().timestamp());
+    }
+
+    fn record_at(&mut self, plugin_id: &str, entry_id: &str, timestamp: i64) {
+        self.activations.push(Activation {
+            plugin_id: String::from(plugin_id),
+            entry_id: String::from(entry_id),
+            timestamp,
+        });
+
+        let overflow = self.activations.len().saturating_sub(MAX_ACTIVATIONS);
+        self.activations.drain(..overflow);
+        self.update_scores();
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
+        std::fs::create_dir_all(&cache_directory)
+            .context("Error while creating cache directory")?;
+
+        let frecency_file =
+            std::fs::File::create(format!("{cache_directory}/{FRECENCY_FILE_NAME}"))
+                .context("Error while creating frecency file")?;
+        serde_json::to_writer(std::io::BufWriter::new(frecency_file), &self.activations)
+            .context("Error while writing frecency file")?;
+
+        Ok(())
+    }
+
+    pub fn score(&self, plugin_id: &str, entry_id: &str) -> f64 {
+        self.scores
+            .get(plugin_id)
+            .and_then(|entry_scores| entry_scores.get(entry_id))
+            .copied()
+            .unwrap_or(0.)
+    }
+
+    /// Score to add to the match score of an entry.
+    pub fn entry_boost(&self, plugin_id: &str, entry_id: &str) -> i64 {
+        (self.score(plugin_id, entry_id).ln_1p() * ENTRY_BOOST_WEIGHT).round() as i64
+    }
+
+    /// Priority to add to the priority of a plugin.
+    pub fn plugin_boost(&self, plugin_id: &str) -> u32 {
+        let plugin_score: f64 = self
+            .scores
+            .get(plugin_id)
+            .map(|entry_scores| entry_scores.values().sum())
+            .unwrap_or(0.);
+        (plugin_score.ln_1p() * PLUGIN_BOOST_WEIGHT).round() as u32
+    }
+
+    fn update_scores(&mut self) {
+        let now = chrono::Utc::now().timestamp();
+        self.scores.clear();
+
+        for activation in self.activations.iter() {
+            let age_in_seconds = (now - activation.timestamp).max(0) as f64;
+            let weight = 0.5_f64.powf(age_in_seconds / HALF_LIFE_IN_SECONDS);
+
+            *self
+                .scores
+                .entry(activation.plugin_id.clone())
+                .or_default()
+                .entry(activation.entry_id.clone())
+                .or_default() += weight;
+        }
+    }
+}
+
+fn read_activations() -> anyhow::Result<Vec<Activation>> {
+    let cache_directory = crate::plugin::utils::centerpiece_cache_directory()?;
+    let frecency_file = std::fs::File::open(format!("{cache_directory}/{FRECENCY_FILE_NAME}"))
+        .context("Error while opening frecency file")?;
+
+    let reader = std::io::BufReader::new(frecency_file);
+    serde_json::from_reader(reader).context("Error while reading frecency file")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Frecency;
+
+    const DAY_IN_SECONDS: i64 = 24 * 60 * 60;
+
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+
+    #[test]
+    fn halves_the_weight_of_activations_every_half_life() {
+        let now = now();
+        let mut frecency = Frecency::default();
+        frecency.record_at("applications", "firefox", now);
+        frecency.record_at("applications", "terminal", now - 7 * DAY_IN_SECONDS);
+        frecency.record_at("applications", "telegram", now - 14 * DAY_IN_SECONDS);
+
+        let firefox = frecency.score("applications", "firefox");
+        let terminal = frecency.score("applications", "terminal");
+        let telegram = frecency.score("applications", "telegram");
+        assert!((firefox - 1.).abs() < 0.01, "{firefox}");
+        assert!((terminal - 0.5).abs() < 0.01, "{terminal}");
+        assert!((telegram - 0.25).abs() < 0.01, "{telegram}");
+        assert_eq!(frecency.score("applications", "unknown"), 0.);
+    }
+
+    #[test]
+    fn ranks_frequent_activations_above_a_single_recent_one() {
+        let now = now();
+        let mut frecency = Frecency::default();
+        frecency.record_at("applications", "firefox", now);
+        for days in 1..=3 {
+            frecency.record_at("applications", "terminal", now - days * DAY_IN_SECONDS);
+        }
+
+        assert!(
+            frecency.entry_boost("applications", "terminal")
+                > frecency.entry_boost("applications", "firefox")
+        );
+        assert!(frecency.entry_boost("applications", "firefox") > 0);
+        assert_eq!(frecency.entry_boost("applications", "unknown"), 0);
+    }
+
+    #[test]
+    fn boosts_plugins_by_the_activations_of_all_their_entries() {
+        let now = now();
+        let mut frecency = Frecency::default();
+        frecency.record_at("applications", "firefox", now);
+        frecency.record_at("applications", "terminal", now);
+        frecency.record_at("applications", "telegram", now);
+        frecency.record_at("brave_history", "https://docs.rs", now);
+        frecency.record_at("brave_history", "https://docs.rs", now);
+
+        // The most activated entry is in the plugin activated less overall.
+        assert!(frecency.plugin_boost("applications") > frecency.plugin_boost("brave_history"));
+        assert!(
+            frecency.entry_boost("brave_history", "https://docs.rs")
+                > frecency.entry_boost("applications", "firefox")
+        );
+        assert_eq!(frecency.plugin_boost("clock"), 0);
+    }
+
+    #[test]
+    fn keeps_only_the_most_recent_activations() {
+        let now = now();
+        let mut frecency = Frecency::default();
+        frecency.record_at("applications", "firefox", now);
+        for _ in 0..super::MAX_ACTIVATIONS {
+            frecency.record_at("applications", "terminal", now);
+        }
+
+        assert_eq!(frecency.activations.len(), super::MAX_ACTIVATIONS);
+        assert_eq!(frecency.score("applications", "firefox"), 0.);
+    }
+
+    #[test]
+    fn shares_the_activations_of_plugins_with_frecency_enabled_only() {
+        let now = now();
+        let mut frecency = Frecency::default();
+        frecency.record_at("applications", "firefox", now);
+        frecency.record_at("clock", "time", now);
+        let plugin_settings: crate::settings::PluginSettings =
+            serde_yaml::from_str("clock:\n  frecency: false\n").unwrap();
+
+        let shared = frecency.enabled_only(&plugin_settings);
+        assert!(shared.score("applications", "firefox") > 0.);
+        assert_eq!(shared.score("clock", "time"), 0.);
+        assert_eq!(shared.plugin_boost("clock"), 0);
+    }
+}
@@ -0,0 +1,702 @@
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL_CASE: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+const BONUS_FIRST_CHARACTER_MULTIPLIER: i64 = 2;
+const BONUS_ACRONYM: i64 = 12;
+
+// Matches that only succeed because of the entry meta are ranked below
+// matches in the title.
+const PENALTY_META_MATCH: i64 = 24;
+
+// Orders typo matches by their number of typos. They rank below all other
+// matches regardless of their score, see `Entry::matched_with_typos`.
+const PENALTY_TYPO: i64 = 512;
+
+// Terms shorter than this have to match without typos.
+const MIN_ONE_TYPO_LENGTH: usize = 4;
+const MIN_TWO_TYPOS_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Match {
+    pub score: i64,
+    /// Character (not byte) indices of the matched characters in the haystack.
+    pub positions: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharacterClass {
+    Lower,
+    Upper,
+    Number,
+    Letter,
+    NonWord,
+}
+
+fn character_class(character: char) -> CharacterClass {
+    if character.is_lowercase() {
+        CharacterClass::Lower
+    } else if character.is_uppercase() {
+        CharacterClass::Upper
+    } else if character.is_numeric() {
+        CharacterClass::Number
+    } else if character.is_alphabetic() {
+        CharacterClass::Letter
+    } else {
+        CharacterClass::NonWord
+    }
+}
+
+fn bonus(previous: CharacterClass, current: CharacterClass) -> i64 {
+    match (previous, current) {
+        (_, CharacterClass::NonWord) => 0,
+        (CharacterClass::NonWord, _) => BONUS_BOUNDARY,
+        (CharacterClass::Lower, CharacterClass::Upper) => BONUS_CAMEL_CASE,
+        (CharacterClass::Number, _) | (_, CharacterClass::Number) if previous != current => {
+            BONUS_CAMEL_CASE
+        }
+        _ => 0,
+    }
+}
+
+static SHARED: std::sync::OnceLock<Folding> = std::sync::OnceLock::new();
+
+/// How characters are made comparable before matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Folding {
+    /// Characters are compared case insensitively.
+    Case,
+    /// Characters are additionally normalized to their compatibility forms and
+    /// stripped of diacritics, so "muller" matches "Müller" and "ｆｏｏ" matches "foo".
+    Unicode,
+}
+
+impl Folding {
+    /// Shares the folding configured in the settings with all plugins. Later
+    /// calls are ignored.
+    pub fn share(search_settings: &crate::settings::SearchSettings) {
+        let folding = match search_settings.unicode_folding {
+            true => Self::Unicode,
+            false => Self::Case,
+        };
+        if SHARED.set(folding).is_err() {
+            log::warn!("The folding is shared already.");
+        }
+    }
+
+    /// The folding shared with [`Self::share`], unicode folding if it was
+    /// never shared.
+    pub fn shared() -> Self {
+        *SHARED.get_or_init(|| Self::Unicode)
+    }
+
+    fn fold_into(self, character: char, folded: &mut Vec<char>) {
+        if character.is_ascii() {
+            folded.push(character.to_ascii_lowercase());
+            return;
+        }
+
+        match self {
+            Self::Case => folded.push(character.to_lowercase().next().unwrap_or(character)),
+            Self::Unicode => {
+                unicode_normalization::char::decompose_compatible(character, |decomposed| {
+                    if unicode_normalization::char::is_combining_mark(decomposed) {
+                        return;
+                    }
+                    for lowercase in decomposed.to_lowercase() {
+                        match lowercase {
+                            // Letters without a decomposition into a base letter and a mark.
+                            'ı' => folded.push('i'),
+                            'ø' => folded.push('o'),
+                            'ł' => folded.push('l'),
+                            'đ' => folded.push('d'),
+                            'ß' => folded.extend(['s', 's']),
+                            'æ' => folded.extend(['a', 'e']),
+                            'œ' => folded.extend(['o', 'e']),
+                            _ if unicode_normalization::char::is_combining_mark(lowercase) => {}
+                            _ => folded.push(lowercase),
+                        }
+                    }
+                })
+            }
+        }
+    }
+
+    /// Folds every character of the text. Returns the folded characters
+    /// together with the index of the character each one originates from.
+    fn fold(self, text: &[char]) -> (Vec<char>, Vec<usize>) {
+        let mut folded = Vec::with_capacity(text.len());
+        let mut origins = Vec::with_capacity(text.len());
+        for (index, character) in text.iter().enumerate() {
+            self.fold_into(*character, &mut folded);
+            origins.resize(folded.len(), index);
+        }
+        (folded, origins)
+    }
+}
+
+/// Maps positions in the folded text back to the original characters.
+fn to_origins(positions: &[usize], origins: &[usize]) -> Vec<usize> {
+    let mut original_positions: Vec<usize> = positions
+        .iter()
+        .map(|position| origins[*position])
+        .collect();
+    original_positions.dedup();
+    original_positions
+}
+
+/// Scores how well `needle` matches `haystack` as a case insensitive subsequence.
+///
+/// Whitespace in the needle is ignored, so "fire fox" matches "Firefox".
+/// Matches on word boundaries, camel case humps and consecutive characters
+/// score higher than scattered ones. Returns `None` if the needle is not a
+/// subsequence of the haystack.
+pub fn fuzzy_match(haystack: &str, needle: &str, folding: Folding) -> Option<Match> {
+    let needle: Vec<char> = needle
+        .chars()
+        .filter(|character| !character.is_whitespace())
+        .collect();
+    let (needle, _) = folding.fold(&needle);
+    if needle.is_empty() {
+        return Some(Match::default());
+    }
+
+    let haystack: Vec<char> = haystack.chars().collect();
+    let (folded_haystack, origins) = folding.fold(&haystack);
+
+    // Cheap rejection and narrowing of the search window before the quadratic pass.
+    let mut needle_index = 0;
+    let mut window_start = None;
+    for (haystack_index, character) in folded_haystack.iter().enumerate() {
+        if *character == needle[needle_index] {
+            window_start.get_or_insert(haystack_index);
+            needle_index += 1;
+            if needle_index == needle.len() {
+                break;
+            }
+        }
+    }
+    if needle_index < needle.len() {
+        return None;
+    }
+    let window_start = window_start?;
+
+    // Characters folded into several ones only get the bonus of the original
+    // character on the first of them.
+    let bonuses: Vec<i64> = origins
+        .iter()
+        .enumerate()
+        .map(|(folded_index, index)| {
+            let previous = match (*index, folded_index) {
+                (0, _) => CharacterClass::NonWord,
+                (_, 0) => character_class(haystack[index - 1]),
+                _ if origins[folded_index - 1] == *index => return 0,
+                _ => character_class(haystack[index - 1]),
+            };
+            bonus(previous, character_class(haystack[*index]))
+        })
+        .collect();
+
+    let length = folded_haystack.len();
+    let mut scores: Vec<Vec<Option<i64>>> = vec![vec![None; length]; needle.len()];
+    let mut predecessors: Vec<Vec<usize>> = vec![vec![0; length]; needle.len()];
+
+    for (haystack_index, character) in folded_haystack.iter().enumerate().skip(window_start) {
+        if *character == needle[0] {
+            scores[0][haystack_index] =
+                Some(SCORE_MATCH + bonuses[haystack_index] * BONUS_FIRST_CHARACTER_MULTIPLIER);
+        }
+    }
+
+    for needle_index in 1..needle.len() {
+        // Best score of a match for the previous needle character that is
+        // separated from the current haystack index by a gap.
+        let mut best_gap: Option<(i64, usize)> = None;
+
+        for haystack_index in (window_start + needle_index)..length {
+            if haystack_index >= 2 {
+                let extended_gap =
+                    best_gap.map(|(score, position)| (score + SCORE_GAP_EXTENSION, position));
+                let started_gap = scores[needle_index - 1][haystack_index - 2]
+                    .map(|score| (score + SCORE_GAP_START, haystack_index - 2));
+                best_gap = match (extended_gap, started_gap) {
+                    (Some(extended), Some(started)) if started.0 >= extended.0 => Some(started),
+                    (Some(extended), _) => Some(extended),
+                    (None, started) => started,
+                };
+            }
+
+            if folded_haystack[haystack_index] != needle[needle_index] {
+                continue;
+            }
+
+            let consecutive = scores[needle_index - 1][haystack_index - 1]
+                .map(|score| (score + BONUS_CONSECUTIVE, haystack_index - 1));
+            let best_predecessor = match (consecutive, best_gap) {
+                (Some(consecutive), Some(gap)) if gap.0 > consecutive.0 => Some(gap),
+                (Some(consecutive), _) => Some(consecutive),
+                (None, gap) => gap,
+            };
+
+            if let Some((score, position)) = best_predecessor {
+                scores[needle_index][haystack_index] =
+                    Some(score + SCORE_MATCH + bonuses[haystack_index]);
+                predecessors[needle_index][haystack_index] = position;
+            }
+        }
+    }
+
+    let (mut position, mut score) = scores[needle.len() - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(position, score)| score.map(|score| (position, score)))
+        .max_by(|(a_position, a_score), (b_position, b_score)| {
+            a_score.cmp(b_score).then(b_position.cmp(a_position))
+        })?;
+
+    let mut positions = vec![0; needle.len()];
+    for needle_index in (0..needle.len()).rev() {
+        positions[needle_index] = position;
+        position = predecessors[needle_index][position];
+    }
+
+    let is_acronym = needle.len() > 1
+        && positions
+            .iter()
+            .all(|position| bonuses[*position] >= BONUS_CAMEL_CASE);
+    if is_acronym {
+        score += BONUS_ACRONYM;
+    }
+
+    Some(Match {
+        score,
+        positions: to_origins(&positions, &origins),
+    })
+}
+
+/// Matches `needle` as one case insensitive substring of `haystack`.
+pub fn exact_match(haystack: &str, needle: &str, folding: Folding) -> Option<Match> {
+    let needle: Vec<char> = needle.chars().collect();
+    let (needle, _) = folding.fold(&needle);
+    if needle.is_empty() {
+        return Some(Match::default());
+    }
+
+    let haystack: Vec<char> = haystack.chars().collect();
+    let (folded_haystack, origins) = folding.fold(&haystack);
+    let folded_start = folded_haystack
+        .windows(needle.len())
+        .position(|window| window == needle.as_slice())?;
+
+    let start = origins[folded_start];
+    let previous = match start {
+        0 => CharacterClass::NonWord,
+        _ => character_class(haystack[start - 1]),
+    };
+    let length = needle.len() as i64;
+    let score = SCORE_MATCH * length
+        + BONUS_CONSECUTIVE * (length - 1)
+        + bonus(previous, character_class(haystack[start])) * BONUS_FIRST_CHARACTER_MULTIPLIER;
+
+    let positions: Vec<usize> = (folded_start..folded_start + needle.len()).collect();
+    Some(Match {
+        score,
+        positions: to_origins(&positions, &origins),
+    })
+}
+
+/// Whether a term is long enough to be matched with typos.
+pub fn allows_typos(term: &str) -> bool {
+    term.chars().count() >= MIN_ONE_TYPO_LENGTH
+}
+
+fn max_typos(needle_length: usize) -> usize {
+    match needle_length {
+        length if length >= MIN_TWO_TYPOS_LENGTH => 2,
+        length if length >= MIN_ONE_TYPO_LENGTH => 1,
+        _ => 0,
+    }
+}
+
+/// Matches needles as substrings of haystacks with up to two typos, i.e.
+/// inserted, missing, replaced or swapped characters. Longer needles allow
+/// more typos. The score is negative and decreases with the number of typos.
+///
+/// The buffers are kept between calls, so matching many entries does not
+/// allocate for every one of them.
+pub struct TypoMatcher {
+    folding: Folding,
+    characters: Vec<char>,
+    needle: Vec<char>,
+    haystack: Vec<char>,
+    origins: Vec<usize>,
+    // Edit distances of needle prefixes to haystack substrings ending at the
+    // current haystack index, together with the start of those substrings.
+    // Only the last three columns are needed for swapped characters.
+    column: Vec<(usize, usize)>,
+    previous_column: Vec<(usize, usize)>,
+    before_previous_column: Vec<(usize, usize)>,
+}
+
+impl TypoMatcher {
+    pub fn new(folding: Folding) -> Self {
+        Self {
+            folding,
+            characters: vec![],
+            needle: vec![],
+            haystack: vec![],
+            origins: vec![],
+            column: vec![],
+            previous_column: vec![],
+            before_previous_column: vec![],
+        }
+    }
+
+    pub fn find(&mut self, haystack: &str, needle: &str) -> Option<Match> {
+        self.needle.clear();
+        for character in needle
+            .chars()
+            .filter(|character| !character.is_whitespace())
+        {
+            self.folding.fold_into(character, &mut self.needle);
+        }
+        if self.needle.is_empty() {
+            return Some(Match::default());
+        }
+        let needle_length = self.needle.len();
+        let max_typos = max_typos(needle_length);
+
+        self.characters.clear();
+        self.characters.extend(haystack.chars());
+        self.haystack.clear();
+        self.origins.clear();
+        for (index, character) in self.characters.iter().enumerate() {
+            self.folding.fold_into(*character, &mut self.haystack);
+            self.origins.resize(self.haystack.len(), index);
+        }
+
+        // Every needle character missing from the haystack costs a typo, so
+        // most haystacks are rejected before computing the edit distances.
+        if self.haystack.len() + max_typos < needle_length {
+            return None;
+        }
+        let missing_characters = self
+            .needle
+            .iter()
+            .filter(|character| !self.haystack.contains(character))
+            .count();
+        if missing_characters > max_typos {
+            return None;
+        }
+
+        let needle = &self.needle;
+        let haystack = &self.haystack;
+        for column in [&mut self.previous_column, &mut self.before_previous_column] {
+            column.clear();
+            column.extend((0..=needle_length).map(|typos| (typos, 0)));
+        }
+        self.column.resize(needle_length + 1, (0, 0));
+        let mut best: Option<(usize, usize, usize)> = None;
+
+        for haystack_index in 0..haystack.len() {
+            let column = &mut self.column;
+            let previous_column = &self.previous_column;
+            let before_previous_column = &self.before_previous_column;
+            column[0] = (0, haystack_index + 1);
+            for needle_index in 1..=needle_length {
+                let is_same = needle[needle_index - 1] == haystack[haystack_index];
+                let (replaced_typos, replaced_start) = previous_column[needle_index - 1];
+                let (missing_typos, missing_start) = column[needle_index - 1];
+                let (inserted_typos, inserted_start) = previous_column[needle_index];
+
+                let mut cell = (replaced_typos + usize::from(!is_same), replaced_start);
+                if missing_typos + 1 < cell.0 {
+                    cell = (missing_typos + 1, missing_start);
+                }
+                if inserted_typos + 1 < cell.0 {
+                    cell = (inserted_typos + 1, inserted_start);
+                }
+                let is_swapped = needle_index >= 2
+                    && haystack_index >= 1
+                    && needle[needle_index - 1] == haystack[haystack_index - 1]
+                    && needle[needle_index - 2] == haystack[haystack_index];
+                if is_swapped {
+                    let (swapped_typos, swapped_start) = before_previous_column[needle_index - 2];
+                    if swapped_typos + 1 < cell.0 {
+                        cell = (swapped_typos + 1, swapped_start);
+                    }
+                }
+                column[needle_index] = cell;
+            }
+
+            let (typos, start) = column[needle_length];
+            let is_better = match best {
+                Some((best_typos, _, _)) => typos < best_typos,
+                None => true,
+            };
+            if typos <= max_typos && is_better {
+                best = Some((typos, start, haystack_index + 1));
+            }
+
+            // The current column becomes the previous one, the oldest one is
+            // overwritten next.
+            std::mem::swap(&mut self.before_previous_column, &mut self.previous_column);
+            std::mem::swap(&mut self.previous_column, &mut self.column);
+        }
+
+        let (typos, start, end) = best?;
+        let positions: Vec<usize> = (start..end).collect();
+        Some(Match {
+            score: -PENALTY_TYPO * typos as i64,
+            positions: to_origins(&positions, &self.origins),
+        })
+    }
+}
+
+/// Matches every term and phrase of the query against the entry title and
+/// falls back to the title together with the entry meta. Entries containing
+/// an excluded word are rejected. Positions always refer to the title.
+pub fn match_entry(
+    entry: &crate::model::Entry,
+    query: &crate::search::query::Query,
+    folding: Folding,
+) -> Option<Match> {
+    match_entry_with(entry, query, folding, &mut |haystack, needle| {
+        fuzzy_match(haystack, needle, folding)
+    })
+}
+
+/// Like [`match_entry`], but tolerates typos in the terms instead of matching
+/// them fuzzily.
+pub fn typo_match_entry(
+    entry: &crate::model::Entry,
+    query: &crate::search::query::Query,
+    typo_matcher: &mut TypoMatcher,
+) -> Option<Match> {
+    let folding = typo_matcher.folding;
+    match_entry_with(entry, query, folding, &mut |haystack, needle| {
+        typo_matcher.find(haystack, needle)
+    })
+}
+
+fn match_entry_with(
+    entry: &crate::model::Entry,
+    query: &crate::search::query::Query,
+    folding: Folding,
+    term_matcher: &mut dyn FnMut(&str, &str) -> Option<Match>,
+) -> Option<Match> {
+    let keywords = format!("{} {}", entry.title, entry.meta);
+    let is_excluded = query
+        .excluded
+        .iter()
+        .any(|excluded| exact_match(&keywords, excluded, folding).is_some());
+    if is_excluded {
+        return None;
+    }
+
+    let title_length = entry.title.chars().count();
+    let mut entry_match = Match::default();
+    let mut phrase_matcher = |haystack: &str, needle: &str| exact_match(haystack, needle, folding);
+
+    for term in query.terms.iter() {
+        let term_match = match_text(&entry.title, &keywords, term, term_matcher)?;
+        add_text_match(&mut entry_match, term_match, title_length);
+    }
+    for phrase in query.phrases.iter() {
+        let phrase_match = match_text(&entry.title, &keywords, phrase, &mut phrase_matcher)?;
+        add_text_match(&mut entry_match, phrase_match, title_length);
+    }
+
+    entry_match.positions.sort_unstable();
+    entry_match.positions.dedup();
+    Some(entry_match)
+}
+
+fn add_text_match(entry_match: &mut Match, text_match: Match, title_length: usize) {
+    entry_match.score += text_match.score;
+    entry_match.positions.extend(
+        text_match
+            .positions
+            .into_iter()
+            .filter(|position| *position < title_length),
+    );
+}
+
+fn match_text(
+    title: &str,
+    keywords: &str,
+    needle: &str,
+    matcher: &mut dyn FnMut(&str, &str) -> Option<Match>,
+) -> Option<Match> {
+    if let Some(title_match) = matcher(title, needle) {
+        return Some(title_match);
+    }
+
+    let keywords_match = matcher(keywords, needle)?;
+    Some(Match {
+        score: keywords_match.score - PENALTY_META_MATCH,
+        positions: keywords_match.positions,
+    })
+}
+
+/// Collapses sorted character positions into ranges of consecutive characters.
+pub fn to_ranges(positions: &[usize]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges: Vec<std::ops::Range<usize>> = vec![];
+    for position in positions {
+        match ranges.last_mut() {
+            Some(range) if range.end == *position => range.end += 1,
+            _ => ranges.push(*position..*position + 1),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exact_match, fuzzy_match, Folding, TypoMatcher};
+
+    fn matches(haystack: &str, needle: &str) -> bool {
+        fuzzy_match(haystack, needle, Folding::Unicode).is_some()
+    }
+
+    fn score(haystack: &str, needle: &str) -> i64 {
+        fuzzy_match(haystack, needle, Folding::Unicode)
+            .unwrap()
+            .score
+    }
+
+    #[test]
+    fn matches_subsequences_only() {
+        assert!(matches("Firefox", "ffx"));
+        assert!(matches("Firefox", "FIREFOX"));
+        assert!(!matches("Firefox", "xff"));
+        assert!(!matches("Firefox", "firefoxes"));
+        assert_eq!(
+            fuzzy_match("Firefox", "ffx", Folding::Unicode)
+                .unwrap()
+                .positions,
+            vec![0, 4, 6]
+        );
+    }
+
+    #[test]
+    fn prefers_word_boundaries() {
+        assert!(score("Open Terminal", "term") > score("Open Determinal", "term"));
+        assert_eq!(
+            fuzzy_match("Shutter Stop", "st", Folding::Unicode)
+                .unwrap()
+                .positions,
+            vec![8, 9]
+        );
+    }
+
+    #[test]
+    fn prefers_acronyms() {
+        let acronym_match = fuzzy_match("Visual Studio Code", "vsc", Folding::Unicode).unwrap();
+        assert_eq!(acronym_match.positions, vec![0, 7, 14]);
+        assert!(score("Visual Studio Code", "vsc") > score("Visual Scope", "vsc"));
+        assert!(score("GitHub Desktop", "ghd") > score("Ghidra", "ghd"));
+    }
+
+    #[test]
+    fn prefers_consecutive_runs() {
+        assert!(score("Firefox", "fire") > score("Fibre Channel", "fire"));
+        assert!(score("Firefox", "fox") > score("Fonts Box", "fox"));
+    }
+
+    #[test]
+    fn ignores_whitespace_in_the_needle() {
+        assert!(matches("Firefox", "fire fox"));
+        assert_eq!(score("Firefox", "fire fox"), score("Firefox", "firefox"));
+    }
+
+    #[test]
+    fn folds_german_umlauts_and_sharp_s() {
+        assert!(matches("Müller", "muller"));
+        assert!(matches("MÜLLER", "müller"));
+        assert!(matches("Straße", "strasse"));
+        assert!(matches("STRASSE", "straße"));
+        assert!(matches("Größe", "grosse"));
+    }
+
+    #[test]
+    fn folds_french_accents_and_ligatures() {
+        assert!(matches("Café Crème", "cafe creme"));
+        assert!(matches("École", "ecole"));
+        assert!(matches("Œuvre", "oeuvre"));
+        assert!(matches("Français", "francais"));
+        // Decomposed input, i.e. "e" followed by a combining acute accent.
+        assert!(matches("Cafe\u{301}", "café"));
+        assert!(exact_match("Crème brûlée", "creme brulee", Folding::Unicode).is_some());
+    }
+
+    #[test]
+    fn folds_turkish_dotted_and_dotless_i() {
+        assert!(matches("İstanbul", "istanbul"));
+        assert!(matches("istanbul", "İstanbul"));
+        assert!(matches("Diyarbakır", "diyarbakir"));
+        assert!(matches("DIYARBAKIR", "diyarbakır"));
+        assert!(matches("ılık", "ILIK"));
+    }
+
+    #[test]
+    fn folds_compatibility_forms() {
+        assert!(matches("Ｆｉｒｅｆｏｘ", "firefox"));
+        assert!(matches("ﬁle manager", "file"));
+        assert!(matches("Version ²", "2"));
+    }
+
+    #[test]
+    fn maps_positions_to_the_original_characters() {
+        let folded_match = fuzzy_match("Straße", "strasse", Folding::Unicode).unwrap();
+        assert_eq!(folded_match.positions, vec![0, 1, 2, 3, 4, 5]);
+
+        let folded_match = fuzzy_match("Cafe\u{301} Bar", "cafe", Folding::Unicode).unwrap();
+        assert_eq!(folded_match.positions, vec![0, 1, 2, 3]);
+
+        let folded_match = exact_match("Die Größe", "grosse", Folding::Unicode).unwrap();
+        assert_eq!(folded_match.positions, vec![4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn keeps_diacritics_significant_without_unicode_folding() {
+        assert!(fuzzy_match("Müller", "muller", Folding::Case).is_none());
+        assert!(fuzzy_match("Müller", "MÜLLER", Folding::Case).is_some());
+        assert!(exact_match("Café", "cafe", Folding::Case).is_none());
+    }
+
+    #[test]
+    fn tolerates_typos_depending_on_the_needle_length() {
+        let mut typo_matcher = TypoMatcher::new(Folding::Unicode);
+        let mut typo = |haystack, needle| typo_matcher.find(haystack, needle);
+
+        assert!(typo("Mozilla Firefox", "frieofx").is_none());
+        assert_eq!(
+            typo("Mozilla Firefox", "fierfox").unwrap().positions,
+            (8..15).collect::<Vec<_>>()
+        );
+        assert!(typo("Spotify", "spotfiy").is_some());
+        assert!(typo("Spotify", "spotxfy").is_some());
+        assert!(typo("Thunderbird", "thuderbrid").is_some());
+        assert!(typo("Gimp", "gimo").is_some());
+        assert!(typo("Gimp", "gmo").is_none());
+        assert!(
+            typo("Steam", "stema").unwrap().score
+                > typo("Thunderbird", "thuderbrid").unwrap().score
+        );
+    }
+
+    #[test]
+    fn rejects_haystacks_missing_too_many_needle_characters() {
+        let mut typo_matcher = TypoMatcher::new(Folding::Unicode);
+        assert!(typo_matcher.find("Fire", "firefox").is_none());
+        assert!(typo_matcher.find("Calculator", "thunderbird").is_none());
+        // Buffers left over from longer haystacks and needles do not leak
+        // into later matches.
+        assert!(typo_matcher.find("Gimp", "gimo").is_some());
+        assert!(typo_matcher.find("Gimp", "gmo").is_none());
+    }
+}
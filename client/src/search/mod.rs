@@ -0,0 +1,4 @@
+pub mod associations;
+pub mod frecency;
+pub mod fuzzy;
+pub mod query;
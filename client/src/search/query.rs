@@ -0,0 +1,309 @@
+/// A parsed search query.
+///
+/// Whitespace separates terms which all have to match, in any order.
+/// Text in double quotes has to match exactly as one phrase and a leading
+/// `-` excludes entries containing the following word or phrase.
+/// Known `field:value` pairs are parsed into filters instead of terms.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    /// The query as typed, without filters.
+    pub text: String,
+    pub terms: Vec<String>,
+    pub phrases: Vec<String>,
+    pub excluded: Vec<String>,
+    pub filters: Filters,
+    /// Numbers the searches of the app in the order they were typed. Plugins
+    /// tag their results with it, so results for outdated queries are dropped.
+    pub generation: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filters {
+    /// `plugin:<id>` limits the search to the plugin with this id.
+    pub plugin: Option<String>,
+    /// `after:<yyyy-mm-dd>` limits the search to entries dated on or after this day.
+    pub after: Option<chrono::NaiveDate>,
+    /// `host:<host>` limits the search to urls on this host or its subdomains.
+    pub host: Option<String>,
+    /// `kind:<kind>` limits the search to entries of this kind, like `url` or `path`.
+    pub kind: Option<String>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut text_tokens: Vec<&str> = vec![];
+        let mut characters = input.char_indices().peekable();
+
+        loop {
+            if characters
+                .next_if(|(_, character)| character.is_whitespace())
+                .is_some()
+            {
+                continue;
+            }
+            let Some((token_start, first_character)) = characters.next() else {
+                break;
+            };
+
+            let is_excluded = first_character == '-'
+                && characters
+                    .peek()
+                    .is_some_and(|(_, character)| !character.is_whitespace());
+            let first_character = match is_excluded {
+                true => characters
+                    .next()
+                    .map(|(_, character)| character)
+                    .unwrap_or(first_character),
+                false => first_character,
+            };
+
+            let is_phrase = first_character == '"';
+            let mut token = String::new();
+            if is_phrase {
+                // An unterminated phrase extends to the end of the input.
+                for (_, character) in characters.by_ref() {
+                    if character == '"' {
+                        break;
+                    }
+                    token.push(character);
+                }
+                token = String::from(token.trim());
+            } else {
+                token.push(first_character);
+                while let Some((_, character)) =
+                    characters.next_if(|(_, character)| !character.is_whitespace())
+                {
+                    token.push(character);
+                }
+            }
+            let token_end = characters
+                .peek()
+                .map(|(index, _)| *index)
+                .unwrap_or(input.len());
+
+            if token.is_empty() {
+                continue;
+            }
+
+            if !is_excluded && !is_phrase && query.filters.parse(&token) {
+                continue;
+            }
+            text_tokens.push(&input[token_start..token_end]);
+
+            match (is_excluded, is_phrase) {
+                (true, _) => query.excluded.push(token),
+                (false, true) => query.phrases.push(token),
+                (false, false) => query.terms.push(token),
+            }
+        }
+
+        query.text = text_tokens.join(" ");
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.phrases.is_empty() && self.excluded.is_empty()
+    }
+}
+
+impl Filters {
+    /// Whether any filter has to be evaluated by the plugin itself.
+    pub fn has_entry_filters(&self) -> bool {
+        self.after.is_some() || self.host.is_some()
+    }
+
+    /// Parses a `field:value` token into the filters. Returns false if the
+    /// token is no known filter.
+    fn parse(&mut self, token: &str) -> bool {
+        let Some((field, value)) = token.split_once(':') else {
+            return false;
+        };
+        if value.is_empty() {
+            return false;
+        }
+
+        match field {
+            "plugin" => {
+                self.plugin = Some(String::from(crate::plugin::registry::current_id(value)))
+            }
+            "host" => self.host = Some(value.to_lowercase()),
+            "kind" => {
+                let kind = value.to_lowercase();
+                if !crate::model::EntryKind::NAMES.contains(&kind.as_str()) {
+                    return false;
+                }
+                self.kind = Some(kind);
+            }
+            "after" => match chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                Ok(date) => self.after = Some(date),
+                Err(_) => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Strips a plugin keyword prefix, e.g. "w" from "w home", from the input.
+/// Word prefixes have to be followed by whitespace, symbol prefixes like
+/// "=" may be followed directly by the query.
+pub fn strip_scope_prefix<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    let remainder = input.strip_prefix(prefix)?;
+    let prefix_is_symbol = !prefix.ends_with(char::is_alphanumeric);
+    if !prefix_is_symbol && !remainder.is_empty() && !remainder.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(remainder.trim_start())
+}
+
+/// Extracts the host from an url like `https://user@docs.github.com:443/path`.
+pub fn url_host(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_and_port = authority.rsplit('@').next()?;
+    let host = match host_and_port.starts_with('[') {
+        true => host_and_port.split_inclusive(']').next()?,
+        false => host_and_port.split(':').next()?,
+    };
+
+    match host.is_empty() {
+        true => None,
+        false => Some(host.to_lowercase()),
+    }
+}
+
+/// Whether `url` is on the filtered host or one of its subdomains.
+pub fn matches_host(url: &str, host_filter: &str) -> bool {
+    match url_host(url) {
+        Some(host) => host == host_filter || host.ends_with(&format!(".{host_filter}")),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Query;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| String::from(*value)).collect()
+    }
+
+    #[test]
+    fn parses_terms_in_any_order() {
+        let query = Query::parse("  github   rust ");
+        assert_eq!(query.terms, strings(&["github", "rust"]));
+        assert!(query.phrases.is_empty());
+        assert!(query.excluded.is_empty());
+    }
+
+    #[test]
+    fn parses_mixed_terms_phrases_and_exclusions() {
+        let query = Query::parse(r#"rust "exact phrase" -async docs -"old version""#);
+        assert_eq!(query.terms, strings(&["rust", "docs"]));
+        assert_eq!(query.phrases, strings(&["exact phrase"]));
+        assert_eq!(query.excluded, strings(&["async", "old version"]));
+    }
+
+    #[test]
+    fn treats_lone_and_inner_dashes_as_terms() {
+        let query = Query::parse("- foo-bar");
+        assert_eq!(query.terms, strings(&["-", "foo-bar"]));
+        assert!(query.excluded.is_empty());
+    }
+
+    #[test]
+    fn extends_unterminated_phrases_to_the_end() {
+        let query = Query::parse(r#"open "visual studio"#);
+        assert_eq!(query.terms, strings(&["open"]));
+        assert_eq!(query.phrases, strings(&["visual studio"]));
+    }
+
+    #[test]
+    fn ignores_empty_phrases() {
+        assert!(Query::parse(r#""" -"  ""#).is_empty());
+    }
+
+    #[test]
+    fn keeps_unicode_intact() {
+        let query = Query::parse(r#"Müller "café crème" -straße 日本語 🦀"#);
+        assert_eq!(query.terms, strings(&["Müller", "日本語", "🦀"]));
+        assert_eq!(query.phrases, strings(&["café crème"]));
+        assert_eq!(query.excluded, strings(&["straße"]));
+    }
+
+    #[test]
+    fn accepts_deprecated_plugin_ids() {
+        let query = Query::parse("plugin:sway-windows term");
+        assert_eq!(query.filters.plugin.as_deref(), Some("sway_windows"));
+    }
+
+    #[test]
+    fn parses_field_filters() {
+        let query =
+            Query::parse("plugin:brave_history rust after:2024-01-01 host:GitHub.com kind:URL");
+        assert_eq!(query.terms, strings(&["rust"]));
+        assert_eq!(query.text, "rust");
+        assert_eq!(query.filters.plugin.as_deref(), Some("brave_history"));
+        assert_eq!(
+            query.filters.after,
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+        );
+        assert_eq!(query.filters.host.as_deref(), Some("github.com"));
+        assert_eq!(query.filters.kind.as_deref(), Some("url"));
+    }
+
+    #[test]
+    fn keeps_unknown_and_invalid_filters_as_terms() {
+        let query =
+            Query::parse(r#"after:yesterday http://localhost kind:song "host:x" -plugin:clock"#);
+        assert_eq!(
+            query.terms,
+            strings(&["after:yesterday", "http://localhost", "kind:song"])
+        );
+        assert_eq!(query.phrases, strings(&["host:x"]));
+        assert_eq!(query.excluded, strings(&["plugin:clock"]));
+        assert_eq!(query.filters, super::Filters::default());
+        assert_eq!(
+            query.text,
+            r#"after:yesterday http://localhost kind:song "host:x" -plugin:clock"#
+        );
+    }
+
+    #[test]
+    fn extracts_url_hosts() {
+        assert_eq!(
+            super::url_host("https://user@Docs.GitHub.com:443/path?q=1").as_deref(),
+            Some("docs.github.com")
+        );
+        assert_eq!(super::url_host("file:///home").as_deref(), None);
+        assert!(super::matches_host(
+            "https://gist.github.com/",
+            "github.com"
+        ));
+        assert!(!super::matches_host("https://notgithub.com/", "github.com"));
+    }
+
+    #[test]
+    fn splits_on_unicode_whitespace() {
+        let query = Query::parse("firefox\u{3000}nightly\u{a0}beta");
+        assert_eq!(query.terms, strings(&["firefox", "nightly", "beta"]));
+    }
+
+    #[test]
+    fn strips_word_prefixes_followed_by_whitespace() {
+        assert_eq!(super::strip_scope_prefix("w home", "w"), Some("home"));
+        assert_eq!(super::strip_scope_prefix("w   home ", "w"), Some("home "));
+        assert_eq!(super::strip_scope_prefix("w", "w"), Some(""));
+        assert_eq!(super::strip_scope_prefix("w ", "w"), Some(""));
+        assert_eq!(super::strip_scope_prefix("wifi", "w"), None);
+        assert_eq!(super::strip_scope_prefix("home", "w"), None);
+    }
+
+    #[test]
+    fn strips_symbol_prefixes_without_whitespace() {
+        assert_eq!(super::strip_scope_prefix("=1+2", "="), Some("1+2"));
+        assert_eq!(super::strip_scope_prefix("= 1+2", "="), Some("1+2"));
+        assert_eq!(super::strip_scope_prefix("1=2", "="), None);
+    }
+}
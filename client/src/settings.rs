@@ -4,70 +4,79 @@ fn default_true() -> bool {
     true
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ApplicationsPluginSettings {
+/// Settings every plugin understands, flattened into the settings of each
+/// kind of plugin.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommonPluginSettings {
+    /// Rank frequently and recently used entries of the plugin higher.
     #[serde(default = "default_true")]
-    pub enable: bool,
+    pub frecency: bool,
+    /// Keyword that scopes a query to the plugin, e.g. "w" in "w home".
+    #[serde(default, deserialize_with = "deserialize_prefix")]
+    pub prefix: Option<String>,
 }
 
-impl Default for ApplicationsPluginSettings {
-    fn default() -> Self {
-        Self { enable: true }
-    }
-}
-
-#[derive(Debug, Deserialize)]
-pub struct BraveBookmarksPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
-}
+static DEFAULT_COMMON_PLUGIN_SETTINGS: CommonPluginSettings = CommonPluginSettings {
+    frecency: true,
+    prefix: None,
+};
 
-impl Default for BraveBookmarksPluginSettings {
+impl Default for CommonPluginSettings {
     fn default() -> Self {
-        Self { enable: true }
+        DEFAULT_COMMON_PLUGIN_SETTINGS.clone()
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct BraveHistoryPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
-}
-
-impl Default for BraveHistoryPluginSettings {
-    fn default() -> Self {
-        Self { enable: true }
+// An empty prefix would match every query and scope it to the plugin.
+fn deserialize_prefix<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let prefix: Option<String> = Option::deserialize(deserializer)?;
+    if prefix
+        .as_ref()
+        .is_some_and(|prefix| prefix.trim().is_empty())
+    {
+        return Err(serde::de::Error::custom(
+            "a plugin prefix must not be empty",
+        ));
     }
+    Ok(prefix)
 }
 
-#[derive(Debug, Deserialize)]
-pub struct BraveProgressiveWebAppsSettings {
+/// Settings every built-in plugin understands. Settings specific to a plugin
+/// are kept in `options` until the plugin is spawned, see
+/// `crate::plugin::registry`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BuiltinPluginSettings {
     #[serde(default = "default_true")]
     pub enable: bool,
+    #[serde(flatten)]
+    pub common: CommonPluginSettings,
+    #[serde(flatten)]
+    pub options: serde_yaml::Mapping,
 }
 
-impl Default for BraveProgressiveWebAppsSettings {
+impl Default for BuiltinPluginSettings {
     fn default() -> Self {
-        Self { enable: true }
+        Self {
+            enable: true,
+            common: CommonPluginSettings::default(),
+            options: serde_yaml::Mapping::new(),
+        }
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ClockPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
-}
-
-impl Default for ClockPluginSettings {
-    fn default() -> Self {
-        Self { enable: true }
+impl BuiltinPluginSettings {
+    /// Deserializes the settings specific to the plugin.
+    pub fn options<T: serde::de::DeserializeOwned>(&self) -> anyhow::Result<T> {
+        serde_yaml::from_value(serde_yaml::Value::Mapping(self.options.clone()))
+            .map_err(anyhow::Error::from)
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct GitRepositoriesPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
     #[serde(default = "default_commands")]
     pub commands: Vec<Vec<String>>,
 }
@@ -88,166 +97,201 @@ fn default_commands() -> Vec<Vec<String>> {
     ]
 }
 
-impl Default for GitRepositoriesPluginSettings {
-    fn default() -> Self {
-        Self {
-            enable: true,
-            commands: default_commands(),
-        }
-    }
+/// A plugin driven by a subprocess speaking line delimited JSON, see
+/// `crate::plugin::external`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalPluginSettings {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub priority: u32,
+    pub command: Vec<String>,
+    /// Seconds between `timeout` requests sent to the plugin.
+    #[serde(default)]
+    pub update_interval: Option<u64>,
+    #[serde(flatten)]
+    pub common: CommonPluginSettings,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ResourceMonitorBatteryPluginSettings {
+/// Settings of a script plugin in the `plugins` config directory, keyed by
+/// the script file name without extension.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptPluginSettings {
     #[serde(default = "default_true")]
     pub enable: bool,
+    /// Defaults to the script file name without extension.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub priority: u32,
+    /// Seconds between reloading the entries of the script.
+    #[serde(default)]
+    pub update_interval: Option<u64>,
+    #[serde(flatten)]
+    pub common: CommonPluginSettings,
 }
 
-impl Default for ResourceMonitorBatteryPluginSettings {
+impl Default for ScriptPluginSettings {
     fn default() -> Self {
-        Self { enable: true }
+        Self {
+            enable: true,
+            title: None,
+            priority: 0,
+            update_interval: None,
+            common: CommonPluginSettings::default(),
+        }
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ResourceMonitorCpuPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandField {
+    Id,
+    Title,
+    Meta,
 }
 
-impl Default for ResourceMonitorCpuPluginSettings {
-    fn default() -> Self {
-        Self { enable: true }
-    }
+fn default_command_fields() -> Vec<CommandField> {
+    vec![CommandField::Title, CommandField::Meta, CommandField::Id]
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ResourceMonitorDisksSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
+/// A plugin listing the output lines of a command as entries, see
+/// `crate::plugin::command`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandPluginSettings {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub priority: u32,
+    /// Command printing one entry per line.
+    pub list: Vec<String>,
+    /// Splits each line into the `fields`. Without it the whole line is the title.
+    #[serde(default)]
+    pub separator: Option<String>,
+    #[serde(default = "default_command_fields")]
+    pub fields: Vec<CommandField>,
+    /// Command run on activation. `{id}`, `{title}`, `{meta}` and `{line}` are
+    /// replaced by the values of the activated entry.
+    pub activate: Vec<String>,
+    /// Seconds between running the list command again.
+    #[serde(default)]
+    pub update_interval: Option<u64>,
+    #[serde(flatten)]
+    pub common: CommonPluginSettings,
 }
 
-impl Default for ResourceMonitorDisksSettings {
-    fn default() -> Self {
-        Self { enable: true }
-    }
+#[derive(Debug, Default, Deserialize)]
+pub struct PluginSettings {
+    #[serde(default)]
+    pub external: Vec<ExternalPluginSettings>,
+    #[serde(default)]
+    pub scripts: std::collections::HashMap<String, ScriptPluginSettings>,
+    #[serde(default)]
+    pub commands: Vec<CommandPluginSettings>,
+    /// Settings of the built-in plugins, keyed by the settings key of the
+    /// plugin in `crate::plugin::registry`.
+    #[serde(flatten)]
+    pub builtin: std::collections::HashMap<String, BuiltinPluginSettings>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ResourceMonitorMemoryPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
-}
+impl PluginSettings {
+    /// The settings of a built-in plugin, the defaults if not configured.
+    pub fn builtin(&self, settings_key: &str) -> BuiltinPluginSettings {
+        self.builtin.get(settings_key).cloned().unwrap_or_default()
+    }
 
-impl Default for ResourceMonitorMemoryPluginSettings {
-    fn default() -> Self {
-        Self { enable: true }
+    pub fn frecency_enabled(&self, plugin_id: &str) -> bool {
+        self.common(plugin_id).is_some_and(|common| common.frecency)
     }
-}
 
-#[derive(Debug, Deserialize)]
-pub struct SystemPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
-}
+    pub fn prefix(&self, plugin_id: &str) -> Option<&str> {
+        self.common(plugin_id)?.prefix.as_deref()
+    }
 
-impl Default for SystemPluginSettings {
-    fn default() -> Self {
-        Self { enable: true }
+    /// The settings shared by all plugins, `None` for unknown plugins.
+    fn common(&self, plugin_id: &str) -> Option<&CommonPluginSettings> {
+        if let Some(registration) = crate::plugin::registry::find(plugin_id) {
+            return Some(
+                self.builtin
+                    .get(registration.settings_key)
+                    .map_or(&DEFAULT_COMMON_PLUGIN_SETTINGS, |settings| &settings.common),
+            );
+        }
+        if let Some(external) = self
+            .external
+            .iter()
+            .find(|external| external.id == plugin_id)
+        {
+            return Some(&external.common);
+        }
+        if let Some(command) = self.commands.iter().find(|command| command.id == plugin_id) {
+            return Some(&command.common);
+        }
+        self.scripts.get(plugin_id).map(|script| &script.common)
+    }
+
+    /// Adds the default settings for discovered scripts that are not
+    /// configured, so they are known like all other plugins.
+    pub fn add_scripts(&mut self, scripts: &[(String, std::path::PathBuf)]) {
+        for (id, _) in scripts {
+            self.scripts.entry(id.clone()).or_default();
+        }
+    }
+
+    pub fn script(&self, plugin_id: &str) -> ScriptPluginSettings {
+        self.scripts.get(plugin_id).cloned().unwrap_or_default()
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct WifiPluginSettings {
-    #[serde(default = "default_true")]
-    pub enable: bool,
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Entries are grouped by plugin in priority order.
+    #[default]
+    Grouped,
+    /// Entries of all plugins are merged into a single list ranked by score.
+    Flat,
 }
 
-impl Default for WifiPluginSettings {
-    fn default() -> Self {
-        Self { enable: true }
-    }
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Preview {
+    /// No preview pane, plugins are not asked for previews.
+    #[default]
+    Off,
+    /// The preview of the selected entry is shown right of the entries.
+    Right,
+    /// The preview of the selected entry is shown below the entries.
+    Bottom,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct SwayWindowsPluginSettings {
+pub struct SearchSettings {
+    /// Ignore diacritics and compatibility forms, e.g. match "Müller" by "muller".
     #[serde(default = "default_true")]
-    pub enable: bool,
+    pub unicode_folding: bool,
 }
 
-impl Default for SwayWindowsPluginSettings {
+impl Default for SearchSettings {
     fn default() -> Self {
-        Self { enable: true }
+        Self {
+            unicode_folding: true,
+        }
     }
 }
 
 #[derive(Debug, Default, Deserialize)]
-pub struct PluginSettings {
-    #[serde(default)]
-    pub applications: ApplicationsPluginSettings,
-    #[serde(default)]
-    pub brave_bookmarks: BraveBookmarksPluginSettings,
-    #[serde(default)]
-    pub brave_history: BraveHistoryPluginSettings,
-    #[serde(default)]
-    pub brave_progressive_web_apps: BraveProgressiveWebAppsSettings,
-    #[serde(default)]
-    pub clock: ClockPluginSettings,
-    #[serde(default)]
-    pub git_repositories: GitRepositoriesPluginSettings,
-    #[serde(default)]
-    pub resource_monitor_battery: ResourceMonitorBatteryPluginSettings,
-    #[serde(default)]
-    pub resource_monitor_cpu: ResourceMonitorCpuPluginSettings,
-    #[serde(default)]
-    pub resource_monitor_disks: ResourceMonitorDisksSettings,
-    #[serde(default)]
-    pub resource_monitor_memory: ResourceMonitorMemoryPluginSettings,
+pub struct Settings {
     #[serde(default)]
-    pub sway_windows: SwayWindowsPluginSettings,
+    pub layout: Layout,
     #[serde(default)]
-    pub system: SystemPluginSettings,
+    pub preview: Preview,
     #[serde(default)]
-    pub wifi: WifiPluginSettings,
-}
-
-#[derive(Debug, Default, Deserialize)]
-pub struct Settings {
+    pub search: SearchSettings,
     #[serde(default)]
     pub plugin: PluginSettings,
 }
 
-impl Settings {
-    pub fn new() -> Self {
-        let config_directory_result = crate::plugin::utils::centerpiece_config_directory();
-        if let Err(error) = config_directory_result {
-            log::error!(
-            error = log::error!("{:?}", error);
-            "Unable to find config directory.",
-            );
-            panic!();
-        }
-        let config_directory = config_directory_result.unwrap();
-        let config_file_path = format!("{config_directory}/config.yml");
-
-        let config_file_result = std::fs::File::open(config_file_path);
-        if config_file_result.is_err() {
-            log::info!("No custom config file found, falling back to default.");
-            return Self::default();
-        }
-        let config_file = config_file_result.unwrap();
-        let config_result = serde_yaml::from_reader(config_file);
-        if let Err(error) = config_result {
-            log::error!(
-            error = log::error!("{:?}", error);
-            "Config file does not match settings struct.",
-            );
-            panic!();
-        }
-        config_result.unwrap()
-    }
-}
-
 impl std::convert::TryFrom<crate::cli::CliArgs> for Settings {
     type Error = anyhow::Error;
 
@@ -275,6 +319,60 @@ impl std::convert::TryFrom<crate::cli::CliArgs> for Settings {
             "Config file does not match settings struct.",
             );
         }
-        Ok(config_result?)
+        let settings: Self = config_result?;
+
+        let check_result = crate::plugin::registry::check_settings(&settings.plugin);
+        if let Err(ref error) = check_result {
+            log::error!("{:?}", error);
+        }
+        check_result?;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn parse(yaml: &str) -> Result<super::PluginSettings, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+
+    #[test]
+    fn reads_common_settings_next_to_plugin_options() {
+        let settings = parse(
+            "git_repositories:\n  prefix: g\n  frecency: false\n  commands: [[code, $GIT_DIRECTORY]]\n",
+        )
+        .unwrap();
+        let git_repositories = settings.builtin("git_repositories");
+        assert_eq!(git_repositories.common.prefix.as_deref(), Some("g"));
+        assert!(!git_repositories.common.frecency);
+        assert!(git_repositories.options.contains_key("commands"));
+        assert!(!git_repositories.options.contains_key("prefix"));
+    }
+
+    #[test]
+    fn rejects_empty_prefixes() {
+        assert!(parse("clock:\n  prefix: \"\"\n").is_err());
+        assert!(parse("scripts:\n  notes:\n    prefix: \" \"\n").is_err());
+        assert!(parse("clock:\n  prefix: null\n").is_ok());
+    }
+
+    #[test]
+    fn knows_the_common_settings_of_known_plugins_only() {
+        let mut settings = parse(
+            "scripts:\n  notes:\n    frecency: false\n    prefix: n\ncommands:\n  - id: files\n    title: Files\n    list: [ls]\n    activate: [xdg-open, \"{line}\"]\n    prefix: f\n",
+        )
+        .unwrap();
+        settings.add_scripts(&[(String::from("docs"), std::path::PathBuf::from("docs.rhai"))]);
+
+        assert!(settings.frecency_enabled("clock"));
+        assert!(settings.frecency_enabled("files"));
+        assert!(settings.frecency_enabled("docs"));
+        assert!(!settings.frecency_enabled("notes"));
+        assert!(!settings.frecency_enabled("unknown"));
+        assert_eq!(settings.prefix("notes"), Some("n"));
+        assert_eq!(settings.prefix("files"), Some("f"));
+        assert_eq!(settings.prefix("docs"), None);
+        assert_eq!(settings.prefix("unknown"), None);
     }
 }
@@ -7,6 +7,33 @@ in {
   options.programs.centerpiece = {
     enable = lib.mkEnableOption (lib.mdDoc "Centerpiece");
 
+    config.layout = lib.mkOption {
+      default = "grouped";
+      type = lib.types.enum [ "grouped" "flat" ];
+      description = lib.mdDoc ''
+        How to display results.
+        `grouped` lists entries grouped by plugin.
+        `flat` merges entries of all plugins into one list ranked by match score and plugin priority.
+      '';
+    };
+
+    config.preview = lib.mkOption {
+      default = "off";
+      type = lib.types.enum [ "off" "right" "bottom" ];
+      description = lib.mdDoc ''
+        Where to show details about the selected entry.
+        `off` hides them, `right` and `bottom` place them next to the entries.
+      '';
+    };
+
+    config.search.unicode_folding = lib.mkOption {
+      default = true;
+      type = lib.types.bool;
+      description = lib.mdDoc ''
+        Ignore accents and compatibility forms when matching, e.g. find `Müller` by typing `muller`.
+      '';
+    };
+
     config.plugin = {
       applications = {
         enable = lib.mkOption {
@@ -14,6 +41,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       brave_bookmarks = {
@@ -22,6 +62,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       brave_history = {
@@ -30,6 +83,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       brave_progressive_web_apps = {
@@ -38,6 +104,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       clock = {
@@ -46,6 +125,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       git_repositories = {
@@ -54,6 +146,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
         commands = lib.mkOption {
           default = [
             [ "alacritty" "--command" "nvim" "$GIT_DIRECTORY" ]
@@ -79,6 +184,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       resource_monitor_cpu = {
@@ -87,6 +205,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       resource_monitor_disks = {
@@ -95,6 +226,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       resource_monitor_memory = {
@@ -103,6 +247,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       sway_windows = {
@@ -111,6 +268,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       system = {
@@ -119,6 +289,19 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
       };
 
       wifi = {
@@ -127,6 +310,170 @@ in {
           type = lib.types.bool;
           description = lib.mdDoc "Enable / disable the plugin.";
         };
+        frecency = lib.mkOption {
+          default = true;
+          type = lib.types.bool;
+          description = lib.mdDoc
+            "Rank frequently and recently used entries of the plugin higher.";
+        };
+        prefix = lib.mkOption {
+          default = null;
+          type = lib.types.nullOr lib.types.nonEmptyStr;
+          example = "w";
+          description = lib.mdDoc
+            "Keyword that scopes a query to this plugin, e.g. `w home`.";
+        };
+      };
+
+      commands = lib.mkOption {
+        default = [ ];
+        description = lib.mdDoc
+          "Plugins listing the output lines of a command and running another command with the chosen line.";
+        type = lib.types.listOf (lib.types.submodule {
+          options = {
+            id = lib.mkOption {
+              type = lib.types.str;
+              description = lib.mdDoc "Unique id of the plugin.";
+            };
+            title = lib.mkOption {
+              type = lib.types.str;
+              description = lib.mdDoc "Title shown above the plugin entries.";
+            };
+            priority = lib.mkOption {
+              default = 0;
+              type = lib.types.int;
+              description = lib.mdDoc "Plugins with higher priority are listed first.";
+            };
+            list = lib.mkOption {
+              type = lib.types.listOf lib.types.str;
+              example = [ "sh" "-c" "ls ~/notes" ];
+              description = lib.mdDoc "Command printing one entry per line.";
+            };
+            separator = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.str;
+              example = "\t";
+              description = lib.mdDoc
+                "Splits each line into the `fields`. Without it the whole line is the title.";
+            };
+            fields = lib.mkOption {
+              default = [ "title" "meta" "id" ];
+              type = lib.types.listOf (lib.types.enum [ "id" "title" "meta" ]);
+              description = lib.mdDoc "Order of the fields in a line.";
+            };
+            activate = lib.mkOption {
+              type = lib.types.listOf lib.types.str;
+              example = [ "xdg-open" "{id}" ];
+              description = lib.mdDoc
+                "Command run on activation. `{id}`, `{title}`, `{meta}` and `{line}` are replaced by the values of the entry.";
+            };
+            update_interval = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.int;
+              description = lib.mdDoc "Seconds between running the list command again.";
+            };
+            frecency = lib.mkOption {
+              default = true;
+              type = lib.types.bool;
+              description = lib.mdDoc
+                "Rank frequently and recently used entries of the plugin higher.";
+            };
+            prefix = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.nonEmptyStr;
+              description = lib.mdDoc
+                "Keyword that scopes a query to this plugin, e.g. `w home`.";
+            };
+          };
+        });
+      };
+
+      external = lib.mkOption {
+        default = [ ];
+        description = lib.mdDoc
+          "Plugins driven by a program speaking line delimited JSON on stdin and stdout.";
+        type = lib.types.listOf (lib.types.submodule {
+          options = {
+            id = lib.mkOption {
+              type = lib.types.str;
+              description = lib.mdDoc "Unique id of the plugin.";
+            };
+            title = lib.mkOption {
+              type = lib.types.str;
+              description = lib.mdDoc "Title shown above the plugin entries.";
+            };
+            priority = lib.mkOption {
+              default = 0;
+              type = lib.types.int;
+              description = lib.mdDoc "Plugins with higher priority are listed first.";
+            };
+            command = lib.mkOption {
+              type = lib.types.listOf lib.types.str;
+              example = [ "python3" "/home/me/notes-plugin.py" ];
+              description = lib.mdDoc "Program and arguments starting the plugin.";
+            };
+            update_interval = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.int;
+              description = lib.mdDoc "Seconds between `timeout` requests sent to the plugin.";
+            };
+            frecency = lib.mkOption {
+              default = true;
+              type = lib.types.bool;
+              description = lib.mdDoc
+                "Rank frequently and recently used entries of the plugin higher.";
+            };
+            prefix = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.nonEmptyStr;
+              description = lib.mdDoc
+                "Keyword that scopes a query to this plugin, e.g. `w home`.";
+            };
+          };
+        });
+      };
+
+      scripts = lib.mkOption {
+        default = { };
+        description = lib.mdDoc
+          "Settings of the Rhai script plugins in `~/.config/centerpiece/plugins/`, keyed by file name without extension.";
+        type = lib.types.attrsOf (lib.types.submodule {
+          options = {
+            enable = lib.mkOption {
+              default = true;
+              type = lib.types.bool;
+              description = lib.mdDoc "Enable / disable the plugin.";
+            };
+            title = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.str;
+              description = lib.mdDoc
+                "Title shown above the plugin entries, defaults to the file name.";
+            };
+            priority = lib.mkOption {
+              default = 0;
+              type = lib.types.int;
+              description = lib.mdDoc "Plugins with higher priority are listed first.";
+            };
+            update_interval = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.int;
+              description = lib.mdDoc "Seconds between reloading the entries of the script.";
+            };
+            frecency = lib.mkOption {
+              default = true;
+              type = lib.types.bool;
+              description = lib.mdDoc
+                "Rank frequently and recently used entries of the plugin higher.";
+            };
+            prefix = lib.mkOption {
+              default = null;
+              type = lib.types.nullOr lib.types.nonEmptyStr;
+              description = lib.mdDoc
+                "Keyword that scopes a query to this plugin, e.g. `w home`.";
+            };
+          };
+        });
       };
     };
 